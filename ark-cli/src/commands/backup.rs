@@ -2,8 +2,8 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use crate::{
-    create_dir_all, dir, discover_roots, home_dir, storages_exists, timestamp,
-    AppError, CopyOptions, File, ARK_BACKUPS_PATH, ARK_FOLDER,
+    ark_folder_path, create_dir_all, dir, discover_roots, home_dir,
+    storages_exists, timestamp, AppError, CopyOptions, File, ARK_BACKUPS_PATH,
     ROOTS_CFG_FILENAME,
 };
 
@@ -75,7 +75,7 @@ impl Backup {
                 options.copy_inside = true;
 
                 let result =
-                    dir::copy(root.join(ARK_FOLDER), storage_backup, &options);
+                    dir::copy(ark_folder_path(&root), storage_backup, &options);
 
                 if let Err(e) = result {
                     println!("\t\tFailed to copy storages!\n\t\t{}", e);
@@ -14,6 +14,6 @@ pub struct Collisions {
 
 impl Collisions {
     pub fn run(&self) -> Result<(), AppError> {
-        monitor_index(&self.root_dir, None)
+        monitor_index(&self.root_dir, None, false, false)
     }
 }
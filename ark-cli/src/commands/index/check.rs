@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use fs_index::ResourceIndex;
+
+use crate::{provide_root, AppError, ResourceId};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "check",
+    about = "Check the stored index against the files on disk"
+)]
+pub struct Check {
+    #[clap(value_parser, help = "Path to the root directory")]
+    root_dir: Option<PathBuf>,
+    #[clap(long, action, help = "Update and store the index if outdated")]
+    fix: bool,
+}
+
+impl Check {
+    pub fn run(&self) -> Result<(), AppError> {
+        let root = provide_root(&self.root_dir)?;
+        let load_error =
+            |e| AppError::IndexError(format!("Could not load index: {}", e));
+
+        // loading drops the entries of missing files,
+        // so they are looked up in the stored entries
+        let stored = ResourceIndex::<ResourceId>::load_entries(&root)
+            .map_err(load_error)?;
+        let missing: Vec<_> = stored
+            .iter()
+            .filter(|(path, _)| !root.join(path).exists())
+            .collect();
+        for (path, entry) in missing.iter() {
+            println!("missing  {} {}", entry.id, path.display());
+        }
+
+        let mut index: ResourceIndex<ResourceId> =
+            ResourceIndex::load(&root).map_err(load_error)?;
+        // the maps are shared until the update modifies them
+        let loaded = index.clone();
+        let update = index.update_all()?;
+
+        // a modified file is both deleted and added,
+        // so the entries are matched by path
+        let mut replaced = HashSet::new();
+        for (path, id) in update.added.iter() {
+            match loaded.path2id.get(path) {
+                Some(old) => {
+                    println!(
+                        "modified {} {} -> {}",
+                        path.display(),
+                        old.id,
+                        id
+                    );
+                    replaced.insert(&old.id);
+                }
+                None => println!("unknown  {} {}", id, path.display()),
+            }
+        }
+        let outdated: Vec<_> = update
+            .deleted
+            .iter()
+            .filter(|id| !replaced.contains(id))
+            .collect();
+        for id in outdated.iter() {
+            match loaded.get_resource_by_id_any(id) {
+                Some((path, _)) => {
+                    println!("outdated {} {}", id, path.display())
+                }
+                None => println!("outdated {}", id),
+            }
+        }
+
+        let discrepancies = missing.len() + update.added.len() + outdated.len();
+        if discrepancies == 0 {
+            println!("Stored index is up to date");
+            Ok(())
+        } else if self.fix {
+            index.store()?;
+            println!("Stored index updated with {} changes", discrepancies);
+            Ok(())
+        } else {
+            Err(AppError::IndexError(format!(
+                "{} discrepancies with the stored index",
+                discrepancies
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Check;
+    use crate::ResourceId;
+    use fs_index::ResourceIndex;
+
+    #[test]
+    fn check_should_report_deleted_files() {
+        let root = std::env::temp_dir()
+            .join(format!("ark-cli-check-{}", std::process::id()));
+        std::fs::create_dir_all(&root).expect("Could not create temp dir");
+
+        // the temp dir is removed even if an assertion fails
+        let result = std::panic::catch_unwind(|| {
+            std::fs::write(root.join("kept.txt"), "kept")
+                .expect("Could not create file");
+            std::fs::write(root.join("deleted.txt"), "deleted")
+                .expect("Could not create file");
+
+            let index: ResourceIndex<ResourceId> = ResourceIndex::build(&root);
+            index.store().expect("Could not store index");
+
+            let check = Check {
+                root_dir: Some(root.clone()),
+                fix: false,
+            };
+            assert!(check.run().is_ok());
+
+            std::fs::remove_file(root.join("deleted.txt"))
+                .expect("Could not delete file");
+            assert!(check.run().is_err());
+
+            let index: ResourceIndex<ResourceId> = ResourceIndex::build(&root);
+            index.store().expect("Could not store index");
+
+            // the new timestamp must be past the update threshold
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            std::fs::write(root.join("kept.txt"), "modified")
+                .expect("Could not modify file");
+            let error = check
+                .run()
+                .expect_err("Should report modified file");
+            // counted once, not as both an outdated and an unknown entry
+            assert!(error.to_string().contains("1 discrepancies"));
+        });
+        std::fs::remove_dir_all(&root).expect("Could not clean up");
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
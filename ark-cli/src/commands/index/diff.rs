@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use fs_index::{IndexDiff, ResourceIndex};
+
+use crate::{AppError, ResourceId};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "diff", about = "Compare two stored indexes")]
+pub struct Diff {
+    #[clap(help = "Path to the older index file, e.g. a backup of \
+        ROOT/.ark/index")]
+    path_a: PathBuf,
+    #[clap(help = "Path to the newer index file")]
+    path_b: PathBuf,
+}
+
+impl Diff {
+    pub fn run(&self) -> Result<(), AppError> {
+        // stored paths are compared as they are, so the index files
+        // can be anywhere and the files don't have to exist anymore
+        let diff = IndexDiff::between(
+            read_ids(&self.path_a)?,
+            read_ids(&self.path_b)?,
+        );
+
+        let (green, red, reset) = if std::io::stdout().is_terminal() {
+            (GREEN, RED, RESET)
+        } else {
+            ("", "", "")
+        };
+
+        for (path, id) in diff.removed.iter() {
+            println!("{}- {} {}{}", red, path.display(), id, reset);
+        }
+        for (path, id) in diff.added.iter() {
+            println!("{}+ {} {}{}", green, path.display(), id, reset);
+        }
+        for (path, old_id, new_id) in diff.modified.iter() {
+            println!("{}- {} {}{}", red, path.display(), old_id, reset);
+            println!("{}+ {} {}{}", green, path.display(), new_id, reset);
+        }
+
+        Ok(())
+    }
+}
+
+fn read_ids(index_path: &Path) -> Result<Vec<(PathBuf, ResourceId)>, AppError> {
+    let file = File::open(index_path)?;
+    let entries = ResourceIndex::<ResourceId>::parse_entries(file)?;
+    Ok(entries
+        .into_iter()
+        .map(|(path, entry)| (path, entry.id))
+        .collect())
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{provide_index, provide_root, AppError, ResourceId};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "find", about = "Print the paths of a resource by its ID")]
+pub struct Find {
+    #[clap(help = "ID of the resource")]
+    id: String,
+    #[clap(value_parser, help = "Path to the root directory")]
+    root_dir: Option<PathBuf>,
+    #[clap(long, action, help = "Print only the first match")]
+    first: bool,
+}
+
+impl Find {
+    pub fn run(&self) -> Result<(), AppError> {
+        let id = ResourceId::from_str(&self.id).map_err(|_| {
+            AppError::IndexError(format!("Invalid ID: {}", self.id))
+        })?;
+
+        let root = provide_root(&self.root_dir)?;
+        let index = provide_index(&root).map_err(|_| {
+            AppError::IndexError("Could not provide index".to_owned())
+        })?;
+        let index = index.read().map_err(|_| {
+            AppError::IndexError("Could not read index".to_owned())
+        })?;
+
+        if self.first {
+            let (path, _) =
+                index.get_resource_by_id_any(&id).ok_or_else(|| {
+                    AppError::IndexError(format!("No resource with ID {}", id))
+                })?;
+            println!("{}", path.display());
+            return Ok(());
+        }
+
+        let mut paths: Vec<_> = index
+            .iter()
+            .filter(|(_, entry)| entry.id == id)
+            .map(|(path, _)| path)
+            .collect();
+        paths.sort();
+
+        if paths.is_empty() {
+            return Err(AppError::IndexError(format!(
+                "No resource with ID {}",
+                id
+            )));
+        }
+        if paths.len() > 1 {
+            println!("Collision group of {} paths:", paths.len());
+        }
+        for path in paths {
+            println!("{}", path.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use crate::{provide_index, provide_root, AppError};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "lock",
+    about = "Write a lock file pinning the checksum of every indexed file"
+)]
+pub struct Lock {
+    #[clap(value_parser, help = "Path to the root directory")]
+    root_dir: Option<PathBuf>,
+    #[clap(
+        short,
+        long,
+        help = "Path to the lock file, printed to stdout if omitted"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl Lock {
+    pub fn run(&self) -> Result<(), AppError> {
+        let root = provide_root(&self.root_dir)?;
+        let index = provide_index(&root).map_err(|_| {
+            AppError::IndexError("Could not provide index".to_owned())
+        })?;
+        let index = index.read().map_err(|_| {
+            AppError::IndexError("Could not read index".to_owned())
+        })?;
+
+        match &self.output {
+            Some(path) => index.generate_lock_file(File::create(path)?)?,
+            None => index.generate_lock_file(std::io::stdout())?,
+        }
+
+        Ok(())
+    }
+}
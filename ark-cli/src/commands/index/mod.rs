@@ -0,0 +1,19 @@
+use clap::Subcommand;
+
+mod check;
+mod diff;
+mod find;
+mod lock;
+mod stats;
+mod verify_lock;
+
+/// Available commands for the `index` subcommand
+#[derive(Subcommand, Debug)]
+pub enum Index {
+    Check(check::Check),
+    Diff(diff::Diff),
+    Find(find::Find),
+    Lock(lock::Lock),
+    Stats(stats::Stats),
+    VerifyLock(verify_lock::VerifyLock),
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use fs_storage::{ark_folder_path, INDEX_PATH};
+
+use crate::{provide_index, provide_root, AppError, DateTime, Utc};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(name = "stats", about = "Show statistics of the index")]
+pub struct Stats {
+    #[clap(value_parser, help = "Path to the root directory")]
+    root_dir: Option<PathBuf>,
+    #[clap(long, action, help = "Print the statistics as JSON")]
+    json: bool,
+}
+
+impl Stats {
+    pub fn run(&self) -> Result<(), AppError> {
+        let root = provide_root(&self.root_dir)?;
+        let index = provide_index(&root).map_err(|_| {
+            AppError::IndexError("Could not provide index".to_owned())
+        })?;
+        let stats = index
+            .read()
+            .map_err(|_| {
+                AppError::IndexError("Could not read index".to_owned())
+            })?
+            .statistics();
+
+        // the index might have been built without being stored
+        let index_file =
+            std::fs::metadata(ark_folder_path(&root).join(INDEX_PATH)).ok();
+        let updated = index_file
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok())
+            .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339());
+        let index_size = index_file.map(|metadata| metadata.len());
+
+        if self.json {
+            let json = serde_json::json!({
+                "total_files": stats.total_files,
+                "total_size": stats.total_size,
+                "unique_ids": stats.unique_ids,
+                "collision_groups": stats.collision_groups,
+                "algorithm": stats.algorithm,
+                "last_updated": updated,
+                "index_file_size": index_size,
+            });
+            println!("{}", json);
+            return Ok(());
+        }
+
+        let not_stored = "not stored".to_owned();
+        println!("Total files:      {}", stats.total_files);
+        println!("Total size:       {} bytes", stats.total_size);
+        println!("Unique IDs:       {}", stats.unique_ids);
+        println!("Collision groups: {}", stats.collision_groups);
+        println!("Hash algorithm:   {}", stats.algorithm);
+        println!(
+            "Last updated:     {}",
+            updated.unwrap_or(not_stored.clone())
+        );
+        println!(
+            "Index file size:  {}",
+            index_size
+                .map(|size| format!("{} bytes", size))
+                .unwrap_or(not_stored)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs::File;
+use std::path::PathBuf;
+
+use fs_index::LockMismatch;
+
+use crate::{provide_index, provide_root, AppError};
+
+#[derive(Clone, Debug, clap::Args)]
+#[clap(
+    name = "verify-lock",
+    about = "Check the indexed files against a lock file"
+)]
+pub struct VerifyLock {
+    #[clap(help = "Path to the lock file")]
+    lock_file: PathBuf,
+    #[clap(value_parser, help = "Path to the root directory")]
+    root_dir: Option<PathBuf>,
+}
+
+impl VerifyLock {
+    pub fn run(&self) -> Result<(), AppError> {
+        let root = provide_root(&self.root_dir)?;
+        let index = provide_index(&root).map_err(|_| {
+            AppError::IndexError("Could not provide index".to_owned())
+        })?;
+        let index = index.read().map_err(|_| {
+            AppError::IndexError("Could not read index".to_owned())
+        })?;
+
+        let mismatches =
+            index.verify_lock_file(File::open(&self.lock_file)?)?;
+        for mismatch in mismatches.iter() {
+            match mismatch {
+                LockMismatch::Missing { path } => {
+                    println!("missing   {}", path.display())
+                }
+                LockMismatch::Unlocked { path } => {
+                    println!("unlocked  {}", path.display())
+                }
+                LockMismatch::Changed {
+                    path,
+                    expected,
+                    actual,
+                } => println!(
+                    "changed   {} (expected {}, found {})",
+                    path.display(),
+                    expected,
+                    actual
+                ),
+                LockMismatch::Algorithm {
+                    path,
+                    expected,
+                    actual,
+                } => println!(
+                    "algorithm {} (expected {}, found {})",
+                    path.display(),
+                    expected,
+                    actual
+                ),
+            }
+        }
+
+        if mismatches.is_empty() {
+            println!("Index matches the lock file");
+            Ok(())
+        } else {
+            Err(AppError::IndexError(format!(
+                "{} mismatches with the lock file",
+                mismatches.len()
+            )))
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 
 use crate::{
     provide_index, provide_root, read_storage_value, AppError, DateTime,
-    EntryOutput, File, Sort, StorageEntry, Utc,
+    EntryOutput, File, Sort, SortBy, StorageEntry, Utc,
 };
 
 #[derive(Clone, Debug, clap::Args)]
@@ -39,6 +39,17 @@ pub struct List {
     sort: Option<Sort>,
     #[clap(long, help = "Filter the entries by tag")]
     filter: Option<String>,
+    #[clap(
+        long,
+        value_enum,
+        conflicts_with = "sort",
+        help = "Sort the entries by the given key"
+    )]
+    sort_by: Option<SortBy>,
+    #[clap(long, action, help = "Show only entries with colliding IDs")]
+    duplicates_only: bool,
+    #[clap(long, action, help = "Print the entries as JSON")]
+    json: bool,
 }
 
 impl List {
@@ -68,16 +79,31 @@ impl List {
         let root = provide_root(&self.root_dir)?;
         let entry_output = self.entry()?;
 
-        let mut storage_entries: Vec<StorageEntry> = provide_index(&root)
-            .map_err(|_| {
-                AppError::IndexError("Could not provide index".to_owned())
-            })?
-            .read()
-            .map_err(|_| {
-                AppError::IndexError("Could not read index".to_owned())
-            })?
+        let index = provide_index(&root).map_err(|_| {
+            AppError::IndexError("Could not provide index".to_owned())
+        })?;
+        let index = index.read().map_err(|_| {
+            AppError::IndexError("Could not read index".to_owned())
+        })?;
+
+        let mut resources: Vec<_> = index
             .path2id
             .iter()
+            .filter(|(_, resource)| {
+                !self.duplicates_only
+                    || index.collisions.contains_key(&resource.id)
+            })
+            .collect();
+
+        match self.sort_by {
+            Some(SortBy::Path) => resources.sort_by(|a, b| a.0.cmp(b.0)),
+            Some(SortBy::Id) => resources.sort_by(|a, b| a.1.id.cmp(&b.1.id)),
+            Some(SortBy::Modified) => resources.sort_by_key(|a| a.1.modified),
+            None => (),
+        };
+
+        let mut storage_entries: Vec<StorageEntry> = resources
+            .into_iter()
             .filter_map(|(path, resource)| {
                 let tags = if self.tags {
                     Some(
@@ -186,6 +212,19 @@ impl List {
             });
         }
 
+        if self.json {
+            let entries = storage_entries
+                .iter()
+                .map(entry_to_json)
+                .collect::<Vec<_>>();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&entries)
+                    .map_err(|e| AppError::IndexError(e.to_string()))?
+            );
+            return Ok(());
+        }
+
         let no_tags = "NO_TAGS";
         let no_scores = "NO_SCORE";
 
@@ -346,3 +385,26 @@ impl List {
         Ok(())
     }
 }
+
+fn entry_to_json(entry: &StorageEntry) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    if let Some(path) = &entry.path {
+        object.insert("path".to_owned(), path.display().to_string().into());
+    }
+    if let Some(resource) = &entry.resource {
+        object.insert("id".to_owned(), resource.to_string().into());
+    }
+    if let Some(content) = &entry.content {
+        object.insert("link".to_owned(), content.clone().into());
+    }
+    if let Some(tags) = &entry.tags {
+        object.insert("tags".to_owned(), tags.clone().into());
+    }
+    if let Some(scores) = entry.scores {
+        object.insert("score".to_owned(), scores.into());
+    }
+    if let Some(datetime) = &entry.datetime {
+        object.insert("modified".to_owned(), datetime.clone().into());
+    }
+    serde_json::Value::Object(object)
+}
@@ -3,6 +3,7 @@ use clap::Subcommand;
 mod backup;
 mod collisions;
 pub mod file;
+pub mod index;
 pub mod link;
 mod list;
 mod monitor;
@@ -28,6 +29,11 @@ pub enum Commands {
         #[clap(subcommand)]
         subcommand: file::File,
     },
+    #[command(about = "Manage the index")]
+    Index {
+        #[clap(subcommand)]
+        subcommand: index::Index,
+    },
     #[command(about = "Manage storage")]
     Storage {
         #[clap(subcommand)]
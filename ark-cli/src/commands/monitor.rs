@@ -12,12 +12,25 @@ pub struct Monitor {
         help = "Interval to check for changes in milliseconds"
     )]
     interval: Option<u64>,
+    #[clap(long, action, help = "Print one JSON object per change")]
+    json: bool,
+    #[clap(
+        long,
+        action,
+        help = "Keep retrying while the root directory doesn't exist"
+    )]
+    ignore_initial_errors: bool,
 }
 
 impl Monitor {
     pub fn run(&self) -> Result<(), AppError> {
         // SAFETY: interval is always Some since it has a default value in clap
         let millis = self.interval.unwrap();
-        monitor_index(&self.root_dir, Some(millis))
+        monitor_index(
+            &self.root_dir,
+            Some(millis),
+            self.json,
+            self.ignore_initial_errors,
+        )
     }
 }
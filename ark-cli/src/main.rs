@@ -11,7 +11,7 @@ use data_pdf::{render_preview_page, PDFQuality};
 pub(crate) use dev_hash::Crc32 as ResourceId;
 
 use fs_atomic_versions::app_id;
-use fs_storage::ARK_FOLDER;
+use fs_storage::ark_folder_path;
 
 use anyhow::Result;
 
@@ -27,6 +27,9 @@ use home::home_dir;
 
 use crate::cli::Cli;
 use crate::commands::file::File::{Append, Insert, Read};
+use crate::commands::index::Index::{
+    Check, Diff, Find, Lock, Stats, VerifyLock,
+};
 use crate::commands::link::Link::{Create, Load};
 use crate::commands::Commands::Link;
 use crate::commands::Commands::Storage;
@@ -34,6 +37,7 @@ use crate::commands::Commands::*;
 use crate::models::EntryOutput;
 use crate::models::Format;
 use crate::models::Sort;
+use crate::models::SortBy;
 
 use crate::error::AppError;
 
@@ -80,6 +84,14 @@ async fn run() -> Result<()> {
             Insert(insert) => insert.run()?,
             Read(read) => read.run()?,
         },
+        Index { subcommand } => match subcommand {
+            Check(check) => check.run()?,
+            Diff(diff) => diff.run()?,
+            Find(find) => find.run()?,
+            Lock(lock) => lock.run()?,
+            Stats(stats) => stats.run()?,
+            VerifyLock(verify_lock) => verify_lock.run()?,
+        },
         Storage { subcommand } => match subcommand {
             crate::commands::storage::Storage::List(list) => list.run()?,
         },
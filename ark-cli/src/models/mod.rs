@@ -18,6 +18,13 @@ pub enum Sort {
     Desc,
 }
 
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SortBy {
+    Path,
+    Id,
+    Modified,
+}
+
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub enum Format {
     #[clap(name = "json")]
@@ -1,11 +1,13 @@
 use crate::ResourceId;
-use fs_index::index::ResourceIndex;
+use chrono::Utc;
+use fs_index::index::{IndexUpdate, ResourceIndex};
 use fs_metadata::METADATA_STORAGE_FOLDER;
 use fs_properties::PROPERTIES_STORAGE_FOLDER;
 use fs_storage::{
-    ARK_FOLDER, PREVIEWS_STORAGE_FOLDER, SCORE_STORAGE_FILE, STATS_FOLDER,
+    ark_folder_path, PREVIEWS_STORAGE_FOLDER, SCORE_STORAGE_FILE, STATS_FOLDER,
     TAG_STORAGE_FILE, THUMBNAILS_STORAGE_FOLDER,
 };
+use serde::Serialize;
 use std::env::current_dir;
 use std::fs::{canonicalize, metadata};
 use std::io::BufRead;
@@ -77,21 +79,61 @@ pub fn provide_index(root_dir: &PathBuf) -> ResourceIndex<ResourceId> {
     index.clone()
 }
 
+/// A single event reported by `monitor --json`, printed as one JSON line
+#[derive(Serialize)]
+struct WatchEventJson {
+    event: &'static str,
+    id: Option<String>,
+    path: Option<String>,
+    timestamp: String,
+}
+
 pub fn monitor_index(
     root_dir: &Option<PathBuf>,
     interval: Option<u64>,
+    json: bool,
+    ignore_initial_errors: bool,
 ) -> Result<(), AppError> {
     let dir_path = provide_root(root_dir)?;
 
-    println!("Building index of folder {}", dir_path.display());
-    let start = Instant::now();
+    if !json {
+        println!("Building index of folder {}", dir_path.display());
+    }
+    let mut start = Instant::now();
 
-    let result = crate::provide_index(dir_path);
+    // the root might not exist yet when the monitor is started
+    // before the folder is created, so building is retried until
+    // it exists, any other error is returned
+    let mut waiting = false;
+    let result = loop {
+        match crate::provide_index(&dir_path) {
+            Err(err) if ignore_initial_errors && !dir_path.exists() => {
+                if !waiting {
+                    waiting = true;
+                    if json {
+                        print_event(WatchEventJson {
+                            event: "waiting_for_root",
+                            id: None,
+                            path: Some(dir_path.display().to_string()),
+                            timestamp: Utc::now().to_rfc3339(),
+                        });
+                    } else {
+                        println!("Waiting for the root folder: {}", err);
+                    }
+                }
+                thread::sleep(Duration::from_millis(interval.unwrap_or(1000)));
+                start = Instant::now();
+            }
+            result => break result,
+        }
+    };
     let duration = start.elapsed();
 
     match result {
         Ok(rwlock) => {
-            println!("Build succeeded in {:?}\n", duration);
+            if !json {
+                println!("Build succeeded in {:?}\n", duration);
+            }
 
             if let Some(millis) = interval {
                 let mut index = rwlock.write().map_err(|_| {
@@ -103,12 +145,24 @@ pub fn monitor_index(
                     let pause = Duration::from_millis(millis);
                     thread::sleep(pause);
 
+                    // the clone shares the maps of the index, `update_all`
+                    // copies only the ones it modifies while it is alive
+                    let previous = json.then(|| index.clone());
                     let start = Instant::now();
                     match index.update_all() {
+                        Err(msg) if json => eprintln!("Oops! {}", msg),
                         Err(msg) => println!("Oops! {}", msg),
                         Ok(diff) => {
                             index.store().expect("Could not store index");
                             let duration = start.elapsed();
+
+                            if let Some(previous) = &previous {
+                                for event in watch_events(previous, &diff) {
+                                    print_event(event);
+                                }
+                                continue;
+                            }
+
                             println!("Updating succeeded in {:?}\n", duration);
 
                             if !diff.deleted.is_empty() {
@@ -134,14 +188,59 @@ pub fn monitor_index(
                 }
             }
         }
-        Err(err) => println!("Failure: {:?}", err),
+        Err(err) => return Err(err.into()),
     }
 
     Ok(())
 }
 
+fn print_event(event: WatchEventJson) {
+    let line =
+        serde_json::to_string(&event).expect("Could not serialize event");
+    println!("{}", line);
+}
+
+// A path which was deleted and added again with another id
+// is reported once as "updated"
+fn watch_events(
+    previous: &ResourceIndex<ResourceId>,
+    diff: &IndexUpdate<ResourceId>,
+) -> Vec<WatchEventJson> {
+    let timestamp = Utc::now().to_rfc3339();
+    let mut events = Vec::new();
+
+    for id in &diff.deleted {
+        let path = previous.id2path.get(id);
+        if path.is_some_and(|path| diff.added.contains_key(path)) {
+            continue;
+        }
+        events.push(WatchEventJson {
+            event: "deleted",
+            id: Some(id.to_string()),
+            path: path.map(|path| path.display().to_string()),
+            timestamp: timestamp.clone(),
+        });
+    }
+
+    for (path, id) in &diff.added {
+        let event = if previous.path2id.contains_key(path) {
+            "updated"
+        } else {
+            "added"
+        };
+        events.push(WatchEventJson {
+            event,
+            id: Some(id.to_string()),
+            path: Some(path.display().to_string()),
+            timestamp: timestamp.clone(),
+        });
+    }
+
+    events
+}
+
 pub fn storages_exists(path: &Path) -> bool {
-    let meta = metadata(path.join(ARK_FOLDER));
+    let meta = metadata(ark_folder_path(path));
     if let Ok(meta) = meta {
         return meta.is_dir();
     }
@@ -181,51 +280,34 @@ pub fn translate_storage(
 
     match storage.to_lowercase().as_str() {
         "tags" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
-                .join(TAG_STORAGE_FILE),
+            ark_folder_path(&provide_root(root).ok()?).join(TAG_STORAGE_FILE),
             Some(StorageType::File),
         )),
         "scores" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
-                .join(SCORE_STORAGE_FILE),
+            ark_folder_path(&provide_root(root).ok()?).join(SCORE_STORAGE_FILE),
             Some(StorageType::File),
         )),
         "stats" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
-                .join(STATS_FOLDER),
+            ark_folder_path(&provide_root(root).ok()?).join(STATS_FOLDER),
             Some(StorageType::Folder),
         )),
         "properties" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
+            ark_folder_path(&provide_root(root).ok()?)
                 .join(PROPERTIES_STORAGE_FOLDER),
             Some(StorageType::Folder),
         )),
         "metadata" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
+            ark_folder_path(&provide_root(root).ok()?)
                 .join(METADATA_STORAGE_FOLDER),
             Some(StorageType::Folder),
         )),
         "previews" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
+            ark_folder_path(&provide_root(root).ok()?)
                 .join(PREVIEWS_STORAGE_FOLDER),
             Some(StorageType::Folder),
         )),
         "thumbnails" => Some((
-            provide_root(root)
-                .ok()?
-                .join(ARK_FOLDER)
+            ark_folder_path(&provide_root(root).ok()?)
                 .join(THUMBNAILS_STORAGE_FOLDER),
             Some(StorageType::Folder),
         )),
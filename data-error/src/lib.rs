@@ -1,4 +1,4 @@
-use std::{convert::Infallible, str::Utf8Error};
+use std::{convert::Infallible, path::PathBuf, str::Utf8Error};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, ArklibError>;
@@ -9,10 +9,15 @@ pub enum ArklibError {
     Io(#[from] std::io::Error),
     #[error("Path error: {0}")]
     Path(String),
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(PathBuf),
     #[error("There is some collision: {0}")]
     Collision(String),
     #[error("Parsing error")]
     Parse,
+    /// Data was produced by another hash algorithm: expected, found
+    #[error("Hash algorithm mismatch: expected {0}, found {1}")]
+    AlgorithmMismatch(String, String),
     #[error("Networking error")]
     Network,
     /// Storage error shows label and error message
@@ -5,7 +5,7 @@ use fs_metadata::store_metadata;
 use fs_properties::load_raw_properties;
 use fs_properties::store_properties;
 use fs_properties::PROPERTIES_STORAGE_FOLDER;
-use fs_storage::{ARK_FOLDER, PREVIEWS_STORAGE_FOLDER};
+use fs_storage::{ark_folder_path, PREVIEWS_STORAGE_FOLDER};
 use reqwest::header::HeaderValue;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
@@ -44,9 +44,7 @@ impl<Id: ResourceId> Link<Id> {
     }
 
     fn load_user_data<P: AsRef<Path>>(root: P, id: &Id) -> Result<Properties> {
-        let path = root
-            .as_ref()
-            .join(ARK_FOLDER)
+        let path = ark_folder_path(root.as_ref())
             .join(PROPERTIES_STORAGE_FOLDER)
             .join(id.to_string());
         let file = AtomicFile::new(path)?;
@@ -117,9 +115,7 @@ impl<Id: ResourceId> Link<Id> {
         image_data: Vec<u8>,
         id: &Id,
     ) -> Result<()> {
-        let path = root
-            .as_ref()
-            .join(ARK_FOLDER)
+        let path = ark_folder_path(root.as_ref())
             .join(PREVIEWS_STORAGE_FOLDER)
             .join(id.to_string());
         let file = AtomicFile::new(path)?;
@@ -323,8 +319,7 @@ async fn test_create_link_file() {
         assert_eq!(link.prop.title, "test_title");
 
         let id = Crc32::from_bytes(current_bytes.as_bytes()).unwrap();
-        let path = Path::new(&root)
-            .join(ARK_FOLDER)
+        let path = ark_folder_path(Path::new(&root))
             .join(PREVIEWS_STORAGE_FOLDER)
             .join(id.to_string());
         if path.exists() {
@@ -5,7 +5,7 @@ use core::{fmt::Display, str::FromStr};
 use data_error::Result;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::{fmt::Debug, hash::Hash, path::Path};
+use std::{fmt::Debug, hash::Hash, io::Read, path::Path};
 
 /// This trait defines a generic type representing a resource identifier.
 ///
@@ -26,9 +26,28 @@ pub trait ResourceId:
     + Serialize
     + DeserializeOwned
 {
+    /// Human-readable name of the algorithm, e.g. "crc32" or "blake3",
+    /// stored in the index file and shown to users.
+    ///
+    /// Defaults to the full type name, implementors should override it.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
     /// Computes the resource identifier from the given file path
     fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self>;
 
     /// Computes the resource identifier from the given bytes
     fn from_bytes(data: &[u8]) -> Result<Self>;
+
+    /// Computes the resource identifier from the given stream.
+    ///
+    /// The default implementation reads the whole stream into memory
+    /// and delegates to [`ResourceId::from_bytes`], implementors should
+    /// override it to hash the stream without buffering it
+    fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::from_bytes(&data)
+    }
 }
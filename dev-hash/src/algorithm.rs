@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use core::{fmt::Display, str::FromStr};
+
+use data_error::{ArklibError, Result};
+use data_resource::ResourceId;
+
+use crate::{Blake3, Crc32, Xxh3};
+
+/// Selects one of the defined `ResourceId` types at runtime,
+/// e.g. from a config file, instead of as a generic parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Blake3,
+    Crc32,
+    Xxh3,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Blake3,
+        HashAlgorithm::Crc32,
+        HashAlgorithm::Xxh3,
+    ];
+
+    /// Same as [`ResourceId::name`] of the corresponding type
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Blake3 => Blake3::name(),
+            HashAlgorithm::Crc32 => Crc32::name(),
+            HashAlgorithm::Xxh3 => Xxh3::name(),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = ArklibError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        HashAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(s))
+            .ok_or(ArklibError::Parse)
+    }
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Computes the id of the file with the given algorithm as a lowercase
+/// hex digest, zero-padded to the width of the id, so that the output
+/// has the same format whatever the algorithm is
+pub fn compute(algorithm: HashAlgorithm, path: &Path) -> Result<String> {
+    match algorithm {
+        HashAlgorithm::Blake3 => Blake3::from_path(path).map(|id| id.0),
+        HashAlgorithm::Crc32 => {
+            Crc32::from_path(path).map(|id| format!("{:08x}", id.0))
+        }
+        HashAlgorithm::Xxh3 => {
+            Xxh3::from_path(path).map(|id| format!("{:016x}", id.0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_should_dispatch_to_id_types() {
+        let file_path = Path::new("../test-assets/lena.jpg");
+
+        for algorithm in HashAlgorithm::ALL {
+            assert_eq!(
+                algorithm
+                    .to_string()
+                    .parse::<HashAlgorithm>()
+                    .ok(),
+                Some(algorithm)
+            );
+        }
+        assert!("sha256".parse::<HashAlgorithm>().is_err());
+
+        let crc32 = "CRC32"
+            .parse()
+            .expect("Should parse algorithm name");
+        assert_eq!(compute(crc32, file_path).unwrap(), "342a3d4a");
+        assert_eq!(
+            compute(HashAlgorithm::Xxh3, file_path).unwrap(),
+            format!("{:016x}", Xxh3::from_path(file_path).unwrap().0)
+        );
+        assert_eq!(
+            compute(HashAlgorithm::Blake3, file_path).unwrap(),
+            Blake3::from_path(file_path).unwrap().0
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     fs,
-    io::{BufRead, BufReader},
+    io::{ErrorKind, Read},
     path::Path,
 };
 
@@ -35,23 +35,15 @@ impl Display for Blake3 {
 }
 
 impl ResourceId for Blake3 {
+    fn name() -> &'static str {
+        "blake3"
+    }
+
     fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self> {
         log::debug!("Computing BLAKE3 hash for file: {:?}", file_path.as_ref());
 
         let file = fs::File::open(file_path)?;
-        let mut reader = BufReader::new(file);
-        let mut hasher = Hasher::new();
-        let mut buffer = Vec::new();
-        loop {
-            let bytes_read = reader.read_until(b'\n', &mut buffer)?;
-            if bytes_read == 0 {
-                break;
-            }
-            hasher.update(&buffer);
-            buffer.clear();
-        }
-        let hash = hasher.finalize();
-        Ok(Blake3(encode(hash.as_bytes())))
+        Self::from_reader(file)
     }
 
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
@@ -62,6 +54,24 @@ impl ResourceId for Blake3 {
         let hash = hasher.finalize();
         Ok(Blake3(encode(hash.as_bytes())))
     }
+
+    fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        log::debug!("Computing BLAKE3 hash for stream");
+
+        let mut hasher = Hasher::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(bytes_read) => bytes_read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            hasher.update(&buffer[..bytes_read]);
+        }
+        let hash = hasher.finalize();
+        Ok(Blake3(encode(hash.as_bytes())))
+    }
 }
 
 #[cfg(test)]
@@ -85,5 +95,13 @@ mod tests {
             id,
             Blake3("172b4bf148e858b13dde0fc6613413bcb7552e5c4e5c45195ac6c80f20eb5ff5".to_string())
         );
+
+        let file = fs::File::open(file_path).expect("Failed to open file");
+        let id = <Blake3 as ResourceId>::from_reader(file)
+            .expect("Failed to compute resource identifier");
+        assert_eq!(
+            id,
+            Blake3("172b4bf148e858b13dde0fc6613413bcb7552e5c4e5c45195ac6c80f20eb5ff5".to_string())
+        );
     }
 }
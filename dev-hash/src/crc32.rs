@@ -1,6 +1,6 @@
 use std::{
     fs,
-    io::{BufRead, BufReader},
+    io::{ErrorKind, Read},
     path::Path,
 };
 
@@ -34,22 +34,15 @@ impl Display for Crc32 {
 }
 
 impl ResourceId for Crc32 {
+    fn name() -> &'static str {
+        "crc32"
+    }
+
     fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self> {
         log::debug!("Computing CRC32 hash for file: {:?}", file_path.as_ref());
 
         let file = fs::File::open(file_path)?;
-        let mut reader = BufReader::new(file);
-        let mut hasher = Hasher::new();
-        let mut buffer = Vec::new();
-        loop {
-            let bytes_read = reader.read_until(b'\n', &mut buffer)?;
-            if bytes_read == 0 {
-                break;
-            }
-            hasher.update(&buffer);
-            buffer.clear();
-        }
-        Ok(Crc32(hasher.finalize()))
+        Self::from_reader(file)
     }
 
     fn from_bytes(bytes: &[u8]) -> Result<Self> {
@@ -59,6 +52,23 @@ impl ResourceId for Crc32 {
         hasher.update(bytes);
         Ok(Crc32(hasher.finalize()))
     }
+
+    fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        log::debug!("Computing CRC32 hash for stream");
+
+        let mut hasher = Hasher::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(bytes_read) => bytes_read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            hasher.update(&buffer[..bytes_read]);
+        }
+        Ok(Crc32(hasher.finalize()))
+    }
 }
 
 #[cfg(test)]
@@ -76,5 +86,10 @@ mod tests {
         let id = <Crc32 as ResourceId>::from_bytes(&raw_bytes)
             .expect("Failed to compute resource identifier");
         assert_eq!(id, Crc32(875183434));
+
+        let file = fs::File::open(file_path).expect("Failed to open file");
+        let id = <Crc32 as ResourceId>::from_reader(file)
+            .expect("Failed to compute resource identifier");
+        assert_eq!(id, Crc32(875183434));
     }
 }
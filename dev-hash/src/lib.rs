@@ -1,5 +1,9 @@
+mod algorithm;
 mod blake3;
 mod crc32;
+mod xxh3;
 
+pub use algorithm::{compute, HashAlgorithm};
 pub use blake3::Blake3;
 pub use crc32::Crc32;
+pub use xxh3::Xxh3;
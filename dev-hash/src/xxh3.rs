@@ -0,0 +1,93 @@
+use std::{
+    fs,
+    io::{ErrorKind, Read},
+    path::Path,
+};
+
+use core::{fmt::Display, str::FromStr};
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3 as Hasher};
+
+use data_error::Result;
+use data_resource::ResourceId;
+
+/// Represents a resource identifier using the 64-bit XXH3 algorithm.
+///
+/// Uses [`xxhash_rust`] crate to compute the hash value.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+pub struct Xxh3(pub u64);
+
+impl FromStr for Xxh3 {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Ok(Xxh3(u64::from_str(s)?))
+    }
+}
+
+impl Display for Xxh3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ResourceId for Xxh3 {
+    fn name() -> &'static str {
+        "xxh3"
+    }
+
+    fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        log::debug!("Computing XXH3 hash for file: {:?}", file_path.as_ref());
+
+        let file = fs::File::open(file_path)?;
+        Self::from_reader(file)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        log::debug!("Computing XXH3 hash for bytes");
+
+        Ok(Xxh3(xxh3_64(bytes)))
+    }
+
+    fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        log::debug!("Computing XXH3 hash for stream");
+
+        let mut hasher = Hasher::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(bytes_read) => bytes_read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            hasher.update(&buffer[..bytes_read]);
+        }
+        Ok(Xxh3(hasher.digest()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanity_check() {
+        let file_path = Path::new("../test-assets/lena.jpg");
+        let id = Xxh3::from_path(file_path)
+            .expect("Failed to compute resource identifier");
+        assert_eq!(id, Xxh3(1295389120830194086));
+
+        let raw_bytes = fs::read(file_path).expect("Failed to read file");
+        let id = <Xxh3 as ResourceId>::from_bytes(&raw_bytes)
+            .expect("Failed to compute resource identifier");
+        assert_eq!(id, Xxh3(1295389120830194086));
+
+        let file = fs::File::open(file_path).expect("Failed to open file");
+        let id = <Xxh3 as ResourceId>::from_reader(file)
+            .expect("Failed to compute resource identifier");
+        assert_eq!(id, Xxh3(1295389120830194086));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs::File;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use canonical_path::CanonicalPathBuf;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use dev_hash::Crc32;
+use fs_index::index::{IndexEntry, ResourceIndex};
+use fs_index::ConcurrentResourceIndex;
+use uuid::Uuid;
+
+// Set the number of indexed files and of reading threads here
+const FILE_COUNT: u32 = 10_000;
+const READERS: [usize; 3] = [1, 4, 8];
+// lookups done by every reader in one iteration
+const LOOKUPS: u32 = 10_000;
+
+// Readers look up resources by id while a single writer keeps
+// updating timestamps, comparing a `ResourceIndex` behind a `RwLock`,
+// as it is shared in `ark-cli`, with a `ConcurrentResourceIndex`.
+// Contention only shows up with several cores: on a single CPU the
+// threads take turns, so the numbers only measure the cost of cloning
+// the returned entries and must be taken again on multi-core hardware.
+fn concurrent_index_benchmark(c: &mut Criterion) {
+    let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+    std::fs::create_dir(&root).expect("Could not create temp dir");
+    let modified = SystemTime::now();
+    let resources: Vec<_> = (0..FILE_COUNT)
+        .map(|i| {
+            let path = root.join(i.to_string());
+            File::create(&path).expect("Could not create temp file");
+            let path = CanonicalPathBuf::canonicalize(path).unwrap();
+            (
+                path,
+                IndexEntry {
+                    modified,
+                    id: Crc32(i),
+                },
+            )
+        })
+        .collect();
+    let index = ResourceIndex::from_resources(root.clone(), resources.clone());
+
+    let mut group = c.benchmark_group("concurrent_index_reads");
+    for readers in READERS {
+        let locked = Arc::new(RwLock::new(index.clone()));
+        group.bench_with_input(
+            BenchmarkId::new("rwlock", readers),
+            &readers,
+            |b, readers| {
+                b.iter(|| {
+                    run_readers(
+                        *readers,
+                        |id| {
+                            let index = locked.read().unwrap();
+                            black_box(index.get_resource_by_id_any(id))
+                                .is_some()
+                        },
+                        |(path, entry)| {
+                            let mut index = locked.write().unwrap();
+                            Arc::make_mut(&mut index.path2id)
+                                .insert(path.clone(), entry.clone());
+                        },
+                        &resources,
+                    )
+                });
+            },
+        );
+
+        let concurrent = ConcurrentResourceIndex::from(index.clone());
+        group.bench_with_input(
+            BenchmarkId::new("dashmap", readers),
+            &readers,
+            |b, readers| {
+                b.iter(|| {
+                    run_readers(
+                        *readers,
+                        |id| {
+                            black_box(concurrent.get_resource_by_id_any(id))
+                                .is_some()
+                        },
+                        |(path, entry)| {
+                            concurrent.insert(path.clone(), entry.clone());
+                        },
+                        &resources,
+                    )
+                });
+            },
+        );
+    }
+    group.finish();
+
+    std::fs::remove_dir_all(&root).expect("Could not clean up");
+}
+
+fn run_readers<R, W>(
+    readers: usize,
+    read: R,
+    write: W,
+    resources: &[(CanonicalPathBuf, IndexEntry<Crc32>)],
+) where
+    R: Fn(&Crc32) -> bool + Sync,
+    W: Fn(&(CanonicalPathBuf, IndexEntry<Crc32>)) + Sync,
+{
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for resource in resources.iter().cycle() {
+                if done.load(Ordering::Relaxed) {
+                    break;
+                }
+                write(resource);
+            }
+        });
+
+        let handles: Vec<_> = (0..readers)
+            .map(|reader| {
+                let read = &read;
+                scope.spawn(move || {
+                    (0..LOOKUPS)
+                        .map(|i| Crc32((i * 31 + reader as u32) % FILE_COUNT))
+                        .filter(|id| read(id))
+                        .count()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), LOOKUPS as usize);
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = concurrent_index_benchmark
+}
+criterion_main!(benches);
@@ -1,8 +1,15 @@
+use std::fs::File;
+use std::time::SystemTime;
+
+use canonical_path::CanonicalPathBuf;
 use criterion::{
-    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+    black_box, criterion_group, criterion_main, measurement::WallTime,
+    BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
 };
-use dev_hash::Crc32;
-use fs_index::index::ResourceIndex;
+use data_resource::ResourceId;
+use dev_hash::{Blake3, Crc32, Xxh3};
+use fs_index::index::{IndexEntry, ResourceIndex};
+use uuid::Uuid;
 
 const DIR_PATH: &str = "../test-assets/"; // Set the path to the directory containing the resources here
 
@@ -17,27 +24,77 @@ fn index_build_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("index_build");
     group.measurement_time(std::time::Duration::from_secs(20)); // Set the measurement time here
 
+    bench_index_build::<Crc32>(&mut group, "crc32");
+    bench_index_build::<Blake3>(&mut group, "blake3");
+    bench_index_build::<Xxh3>(&mut group, "xxh3");
+
+    group.finish();
+}
+
+fn bench_index_build<Id: ResourceId + Send + Sync>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    algorithm: &str,
+) {
     let mut collisions_size = 0;
 
     group.bench_with_input(
-        BenchmarkId::new("index_build", DIR_PATH),
+        BenchmarkId::new(format!("index_build_{}", algorithm), DIR_PATH),
         &DIR_PATH,
         |b, path| {
             b.iter(|| {
-                let index: ResourceIndex<Crc32> =
+                let index: ResourceIndex<Id> =
                     ResourceIndex::build(black_box(path.to_string()));
                 collisions_size = index.collisions.len();
             });
         },
     );
-    group.finish();
 
-    println!("Collisions: {}", collisions_size);
+    println!("Collisions ({}): {}", algorithm, collisions_size);
+}
+
+// Populating the maps only, the files are created
+// and canonicalized beforehand and never hashed
+fn index_from_resources_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("index_from_resources");
+    for count in [10_000u32, 100_000] {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir(&root).expect("Could not create temp dir");
+        let modified = SystemTime::now();
+        let resources: Vec<_> = (0..count)
+            .map(|i| {
+                let path = root.join(i.to_string());
+                File::create(&path).expect("Could not create temp file");
+                let path = CanonicalPathBuf::canonicalize(path).unwrap();
+                let entry = IndexEntry {
+                    modified,
+                    id: Crc32(i),
+                };
+                (path, entry)
+            })
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &resources,
+            |b, resources| {
+                b.iter_batched(
+                    || resources.clone(),
+                    |resources| {
+                        ResourceIndex::from_resources(root.clone(), resources)
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+
+        std::fs::remove_dir_all(&root).expect("Could not clean up");
+    }
+    group.finish();
 }
 
 criterion_group! {
     name = benches;
     config = Criterion::default();
-    targets = index_build_benchmark
+    targets = index_build_benchmark, index_from_resources_benchmark
 }
 criterion_main!(benches);
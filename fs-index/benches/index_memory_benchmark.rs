@@ -0,0 +1,91 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dev_hash::Crc32;
+use fs_index::index::ResourceIndex;
+use uuid::Uuid;
+
+// Criterion only measures time, so heap usage is tracked
+// by wrapping the system allocator
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn record_alloc(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Set the numbers of files to index here
+const FILE_COUNTS: [usize; 2] = [10_000, 100_000];
+
+fn create_files(count: usize) -> PathBuf {
+    let dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+    fs::create_dir(&dir).expect("Could not create temp dir");
+    for i in 0..count {
+        fs::write(dir.join(format!("{}.txt", i)), i.to_string())
+            .expect("Could not create temp file");
+    }
+    dir
+}
+
+fn bench_index_memory(dir: &Path, count: usize) {
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    let index: ResourceIndex<Crc32> = ResourceIndex::build(dir);
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    let retained = ALLOCATED.load(Ordering::Relaxed) - baseline;
+    assert_eq!(index.size(), count);
+
+    println!(
+        "index_memory/{}: peak {} bytes, retained {} bytes, {} bytes per entry",
+        count,
+        peak,
+        retained,
+        retained / count
+    );
+}
+
+fn main() {
+    for count in FILE_COUNTS {
+        let dir = create_files(count);
+        bench_index_memory(&dir, count);
+        fs::remove_dir_all(&dir).expect("Could not clean up");
+    }
+}
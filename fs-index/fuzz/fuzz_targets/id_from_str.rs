@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use dev_hash::{Blake3, Crc32};
+use libfuzzer_sys::fuzz_target;
+
+// ids are parsed from untrusted index files
+fuzz_target!(|data: &str| {
+    let _ = Blake3::from_str(data);
+    let _ = Crc32::from_str(data);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use dev_hash::{Blake3, Crc32};
+use fs_index::ResourceIndex;
+use libfuzzer_sys::fuzz_target;
+
+// parsing a corrupted index must fail gracefully instead of panicking,
+// paths aren't resolved so the result doesn't depend on the filesystem
+fuzz_target!(|data: &[u8]| {
+    let _ = ResourceIndex::<Crc32>::parse_entries(data);
+    let _ = ResourceIndex::<Blake3>::parse_entries(data);
+});
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+pub const ARKIGNORE_FILE: &str = ".arkignore";
+
+/// Exclusion rules collected from `.arkignore` files.
+///
+/// The files use `.gitignore` syntax: one glob pattern per line,
+/// `#` comments and negation with `!`. Rules of a nested `.arkignore`
+/// apply only inside of its directory and take precedence
+/// over the rules of its parents.
+#[derive(Debug, Default)]
+pub struct ArkIgnore {
+    // ordered from the outermost directory to the innermost one
+    matchers: Vec<Gitignore>,
+}
+
+/// Reads `<root>/.arkignore`, nested files are picked up during the walk
+pub fn load_arkignore(root: &Path) -> ArkIgnore {
+    let mut arkignore = ArkIgnore::default();
+    arkignore.enter_dir(root);
+    arkignore
+}
+
+impl ArkIgnore {
+    /// Loads `.arkignore` of the directory, if there is one
+    pub fn enter_dir(&mut self, dir: &Path) {
+        let path = dir.join(ARKIGNORE_FILE);
+        if !path.is_file() {
+            return;
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(e) = builder.add(&path) {
+            log::warn!("Couldn't read {}:\n{}", path.display(), e);
+        }
+        match builder.build() {
+            Ok(matcher) => {
+                log::debug!("Loaded ignore rules from {}", path.display());
+                self.matchers.push(matcher);
+            }
+            Err(e) => {
+                log::warn!("Couldn't parse {}:\n{}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.matchers
+            .iter()
+            .rev()
+            .filter(|matcher| path.starts_with(matcher.path()))
+            .map(|matcher| matcher.matched(path, is_dir))
+            .find(|matched| !matched.is_none())
+            .map(|matched| matched.is_ignore())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_arkignore, ARKIGNORE_FILE};
+    use crate::index::tests::run_test_and_clean_up;
+    use std::fs;
+
+    #[test]
+    fn nested_rules_should_take_precedence() {
+        run_test_and_clean_up(|root| {
+            let nested = root.join("nested");
+            fs::create_dir_all(&nested).expect("Could not create temp dir");
+            fs::write(root.join(ARKIGNORE_FILE), "# comment\n*.log\n")
+                .expect("Could not write .arkignore");
+            fs::write(nested.join(ARKIGNORE_FILE), "!keep.log\n")
+                .expect("Could not write .arkignore");
+
+            let mut arkignore = load_arkignore(&root);
+            assert!(arkignore.is_ignored(&root.join("debug.log"), false));
+            assert!(arkignore.is_ignored(&nested.join("keep.log"), false));
+            assert!(!arkignore.is_ignored(&root.join("notes.txt"), false));
+
+            arkignore.enter_dir(&nested);
+
+            assert!(arkignore.is_ignored(&nested.join("debug.log"), false));
+            assert!(!arkignore.is_ignored(&nested.join("keep.log"), false));
+        })
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use canonical_path::CanonicalPathBuf;
+use dashmap::mapref::entry::Entry;
+use dashmap::{DashMap, DashSet};
+
+use data_error::{ArklibError, Result};
+use data_resource::ResourceId;
+
+use crate::index::{IndexConfig, IndexEntry, IndexUpdate};
+use crate::ResourceIndex;
+
+/// A [`ResourceIndex`] which can be queried and modified through
+/// a shared reference.
+///
+/// An `Arc<RwLock<ResourceIndex>>` blocks every reader while a path is
+/// being updated, here the maps are sharded and only the shard holding
+/// the path is locked.
+///
+/// It is not a drop-in replacement for [`ResourceIndex`]:
+/// * queries return owned values, since a reference would keep
+///   the shard locked, e.g. [`Self::get_resource_by_path`] and
+///   [`Self::get_resource_by_id_any`] clone the entry and the path,
+///   and [`Self::get_paths_by_id`] collects the paths into a `Vec`;
+/// * entries are changed with [`Self::insert`] and [`Self::remove`],
+///   there is no `index_new`, `update_one` or `forget_id`;
+/// * there is no `collision_groups`, [`Self::get_paths_by_id`]
+///   returns the paths of a single id.
+///
+/// Writing a path is atomic: its entry stays locked until both the
+/// path and its id are updated, so a reader never gets a path with
+/// another id. Queries over every entry, like [`Self::size`] or
+/// [`Self::to_hashmap`], are not snapshots and can see some of the
+/// writes made while they run. [`Self::update_all`] and [`Self::store`]
+/// work on a copy of the entries, see their docs.
+#[derive(Debug)]
+pub struct ConcurrentResourceIndex<Id: ResourceId> {
+    path2id: DashMap<CanonicalPathBuf, IndexEntry<Id>>,
+    // every path with the given id, so collisions don't need a counter
+    id2paths: DashMap<Id, DashSet<CanonicalPathBuf>>,
+    root: PathBuf,
+    config: IndexConfig,
+}
+
+impl<Id: ResourceId> ConcurrentResourceIndex<Id> {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn size(&self) -> usize {
+        self.path2id.len()
+    }
+
+    pub fn len_unique(&self) -> usize {
+        self.id2paths.len()
+    }
+
+    // plain mapping from absolute paths to ids
+    pub fn to_hashmap(&self) -> HashMap<PathBuf, Id> {
+        self.path2id
+            .iter()
+            .map(|item| (item.key().as_path().to_path_buf(), item.id.clone()))
+            .collect()
+    }
+
+    // `path` can be either relative to the root of the index
+    // or absolute, it is canonicalized so it must exist
+    pub fn get_resource_by_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<IndexEntry<Id>> {
+        let path = self.resolve_path(path)?;
+        self.path2id.get(&path).map(|entry| entry.clone())
+    }
+
+    // same as `get_resource_by_path`, but fails with
+    // `ResourceNotFound` when the path isn't indexed
+    pub fn get_resource_by_path_or_error<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<IndexEntry<Id>> {
+        self.get_resource_by_path(&path)
+            .ok_or_else(|| ArklibError::ResourceNotFound(path.as_ref().into()))
+    }
+
+    // same rules for `path` as in `get_resource_by_path`
+    pub fn contains_path(&self, path: &Path) -> bool {
+        self.resolve_path(path)
+            .map(|path| self.path2id.contains_key(&path))
+            .unwrap_or(false)
+    }
+
+    pub fn contains_id(&self, id: &Id) -> bool {
+        self.id2paths.contains_key(id)
+    }
+
+    // one of the entries with the given id, without
+    // looking for the other paths in case of collisions
+    pub fn get_resource_by_id_any(
+        &self,
+        id: &Id,
+    ) -> Option<(CanonicalPathBuf, IndexEntry<Id>)> {
+        let path = self
+            .id2paths
+            .get(id)?
+            .iter()
+            .next()
+            .map(|path| path.clone())?;
+        let entry = self.path2id.get(&path)?.clone();
+        Some((path, entry))
+    }
+
+    // every indexed path with the given id
+    pub fn get_paths_by_id(&self, id: &Id) -> Vec<CanonicalPathBuf> {
+        self.id2paths
+            .get(id)
+            .map(|paths| paths.iter().map(|path| path.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    // replaces the entry of `path` if it was indexed already
+    pub fn insert(
+        &self,
+        path: CanonicalPathBuf,
+        entry: IndexEntry<Id>,
+    ) -> Option<IndexEntry<Id>> {
+        log::trace!("[add] {} by path {}", entry.id, path.display());
+
+        // the entry of the path is locked until `id2paths` is updated
+        match self.path2id.entry(path.clone()) {
+            Entry::Occupied(mut occupied) => {
+                if occupied.get().id != entry.id {
+                    self.forget_path(&occupied.get().id, &path);
+                    self.remember_path(&entry.id, path);
+                }
+                Some(occupied.insert(entry))
+            }
+            Entry::Vacant(vacant) => {
+                self.remember_path(&entry.id, path);
+                vacant.insert(entry);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&self, path: &CanonicalPathBuf) -> Option<IndexEntry<Id>> {
+        let occupied = match self.path2id.entry(path.clone()) {
+            Entry::Occupied(occupied) => occupied,
+            Entry::Vacant(_) => return None,
+        };
+        let id = &occupied.get().id;
+        log::trace!("[forget] {} by path {}", id, path.display());
+
+        self.forget_path(id, path);
+        Some(occupied.remove())
+    }
+
+    /// Same as [`ResourceIndex::store`], writes a copy of the entries
+    pub fn store(&self) -> Result<()> {
+        self.to_index().store()
+    }
+
+    /// Same as [`ResourceIndex::load`]
+    pub fn load<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        ResourceIndex::load(root_path).map(Self::from)
+    }
+
+    fn to_index(&self) -> ResourceIndex<Id> {
+        let entries = self
+            .path2id
+            .iter()
+            .map(|item| (item.key().clone(), item.value().clone()));
+        ResourceIndex::from_entries(
+            self.root.clone(),
+            self.config.clone(),
+            entries,
+        )
+    }
+
+    // sets the entry of `path` to `new`, or removes it when `new` is
+    // `None`, but only if the current entry is still `expected`
+    fn replace_if_unchanged(
+        &self,
+        path: &CanonicalPathBuf,
+        expected: Option<&IndexEntry<Id>>,
+        new: Option<IndexEntry<Id>>,
+    ) {
+        match self.path2id.entry(path.clone()) {
+            Entry::Occupied(mut occupied)
+                if Some(occupied.get()) == expected =>
+            {
+                let old_id = occupied.get().id.clone();
+                match new {
+                    Some(entry) => {
+                        if old_id != entry.id {
+                            self.forget_path(&old_id, path);
+                            self.remember_path(&entry.id, path.clone());
+                        }
+                        occupied.insert(entry);
+                    }
+                    None => {
+                        self.forget_path(&old_id, path);
+                        occupied.remove();
+                    }
+                }
+            }
+            Entry::Vacant(vacant) if expected.is_none() => {
+                if let Some(entry) = new {
+                    self.remember_path(&entry.id, path.clone());
+                    vacant.insert(entry);
+                }
+            }
+            _ => log::debug!(
+                "Skipping {}, it was modified during the update",
+                path.display()
+            ),
+        }
+    }
+
+    fn remember_path(&self, id: &Id, path: CanonicalPathBuf) {
+        self.id2paths
+            .entry(id.clone())
+            .or_default()
+            .insert(path);
+    }
+
+    fn forget_path(&self, id: &Id, path: &CanonicalPathBuf) {
+        if let Some(paths) = self.id2paths.get(id) {
+            paths.remove(path);
+        }
+        self.id2paths
+            .remove_if(id, |_, paths| paths.is_empty());
+    }
+
+    fn resolve_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<CanonicalPathBuf> {
+        CanonicalPathBuf::canonicalize(self.root.join(path)).ok()
+    }
+}
+
+impl<Id: ResourceId + Send + Sync> ConcurrentResourceIndex<Id> {
+    /// Same as [`ResourceIndex::update_all`]. The filesystem is compared
+    /// with a copy of the entries, and a change is applied to a path only
+    /// if its entry is still the one in the copy, so paths inserted,
+    /// removed or replaced meanwhile are left as they are. The returned
+    /// update describes the copy, skipped changes are found by the next
+    /// update.
+    pub fn update_all(&self) -> Result<IndexUpdate<Id>> {
+        let mut index = self.to_index();
+        // shared until `update_all` modifies the entries of the copy
+        let before = index.path2id.clone();
+        let update = index.update_all()?;
+
+        for (path, entry) in before.iter() {
+            if !index.path2id.contains_key(path) {
+                self.replace_if_unchanged(path, Some(entry), None);
+            }
+        }
+        for (path, entry) in index.drain() {
+            let expected = before.get(&path);
+            if expected != Some(&entry) {
+                self.replace_if_unchanged(&path, expected, Some(entry));
+            }
+        }
+
+        Ok(update)
+    }
+
+    /// Same as [`ResourceIndex::provide`]
+    pub fn provide<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        ResourceIndex::provide(root_path).map(Self::from)
+    }
+}
+
+impl<Id: ResourceId> From<ResourceIndex<Id>> for ConcurrentResourceIndex<Id> {
+    fn from(mut index: ResourceIndex<Id>) -> Self {
+        let root = std::mem::take(&mut index.root);
+        let config = std::mem::take(&mut index.config);
+
+        let path2id = DashMap::with_capacity(index.size());
+        let id2paths = DashMap::with_capacity(index.len_unique());
+        for (path, entry) in index.drain() {
+            id2paths
+                .entry(entry.id.clone())
+                .or_insert_with(DashSet::new)
+                .insert(path.clone());
+            path2id.insert(path, entry);
+        }
+
+        ConcurrentResourceIndex {
+            path2id,
+            id2paths,
+            root,
+            config,
+        }
+    }
+}
+
+impl<Id: ResourceId> From<ConcurrentResourceIndex<Id>> for ResourceIndex<Id> {
+    fn from(index: ConcurrentResourceIndex<Id>) -> Self {
+        ResourceIndex::from_entries(index.root, index.config, index.path2id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{
+        create_file_at, run_test_and_clean_up, CRC32_1, CRC32_2,
+    };
+    use crate::index::IndexEntry;
+    use crate::{ConcurrentResourceIndex, ResourceIndex};
+    use canonical_path::CanonicalPathBuf;
+    use dev_hash::Crc32;
+    use std::time::SystemTime;
+
+    #[test]
+    fn concurrent_index_should_track_collisions() {
+        run_test_and_clean_up(|dir| {
+            for (name, size) in [("a.txt", 10), ("b.txt", 10), ("c.txt", 11)] {
+                create_file_at(dir.clone(), Some(size), Some(name));
+            }
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let expected = index.to_hashmap();
+            let concurrent = ConcurrentResourceIndex::from(index);
+
+            assert_eq!(concurrent.size(), 3);
+            assert_eq!(concurrent.len_unique(), 2);
+            assert_eq!(concurrent.to_hashmap(), expected);
+            assert_eq!(concurrent.get_paths_by_id(&CRC32_1).len(), 2);
+
+            let a = CanonicalPathBuf::canonicalize(dir.join("a.txt")).unwrap();
+            let c = CanonicalPathBuf::canonicalize(dir.join("c.txt")).unwrap();
+            std::thread::scope(|scope| {
+                scope.spawn(|| concurrent.remove(&a));
+                scope.spawn(|| {
+                    concurrent.insert(
+                        c.clone(),
+                        IndexEntry {
+                            modified: SystemTime::now(),
+                            id: CRC32_1,
+                        },
+                    )
+                });
+            });
+
+            let restored: ResourceIndex<Crc32> = concurrent.into();
+            assert_eq!(restored.size(), 2);
+            assert_eq!(restored.len_unique(), 1);
+            assert!(!restored.contains_id(&CRC32_2));
+            assert_eq!(restored.collisions.get(&CRC32_1), Some(&2));
+        })
+    }
+
+    #[test]
+    fn concurrent_index_should_update_store_and_load() {
+        run_test_and_clean_up(|dir| {
+            create_file_at(dir.clone(), Some(10), Some("a.txt"));
+
+            let concurrent: ConcurrentResourceIndex<Crc32> =
+                ConcurrentResourceIndex::provide(&dir)
+                    .expect("Should build index");
+            std::fs::remove_file(dir.join("a.txt"))
+                .expect("Could not delete file");
+            create_file_at(dir.clone(), Some(11), Some("b.txt"));
+
+            let update = concurrent
+                .update_all()
+                .expect("Should update index");
+            assert!(update.deleted.contains(&CRC32_1));
+            assert_eq!(update.added.len(), 1);
+            assert_eq!(concurrent.size(), 1);
+            assert!(concurrent.contains_id(&CRC32_2));
+            assert!(!concurrent.contains_id(&CRC32_1));
+
+            concurrent.store().expect("Should store index");
+            let loaded: ConcurrentResourceIndex<Crc32> =
+                ConcurrentResourceIndex::load(&dir).expect("Should load index");
+            assert_eq!(loaded.to_hashmap(), concurrent.to_hashmap());
+        })
+    }
+
+    #[test]
+    fn concurrent_index_should_keep_paths_inserted_during_update() {
+        run_test_and_clean_up(|dir| {
+            for i in 0..20 {
+                create_file_at(dir.clone(), Some(11), Some(&i.to_string()));
+            }
+            let concurrent: ConcurrentResourceIndex<Crc32> =
+                ResourceIndex::build(&dir).into();
+
+            for i in 0..20 {
+                // empty files are never indexed by `update_all` itself,
+                // so only the insertion can add them
+                let (file, file_path) = create_file_at(dir.clone(), None, None);
+                let path = CanonicalPathBuf::canonicalize(file_path)
+                    .expect("Should canonicalize path");
+                let entry = IndexEntry {
+                    modified: file
+                        .metadata()
+                        .and_then(|metadata| metadata.modified())
+                        .expect("Should read modification time"),
+                    id: Crc32(i),
+                };
+
+                std::thread::scope(|scope| {
+                    scope.spawn(|| concurrent.update_all());
+                    scope.spawn(|| concurrent.insert(path.clone(), entry));
+                });
+
+                assert!(concurrent.contains_path(path.as_path()));
+                assert!(concurrent.contains_id(&Crc32(i)));
+            }
+            assert_eq!(concurrent.size(), 40);
+        })
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use data_error::Result;
+use data_resource::ResourceId;
+
+use crate::ResourceIndex;
+
+/// Differences between two indexes, paths are relative to their roots
+/// so that indexes of different roots can be compared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexDiff<Id: ResourceId> {
+    /// Paths present only in the newer index
+    pub added: Vec<(PathBuf, Id)>,
+    /// Paths present only in the older index
+    pub removed: Vec<(PathBuf, Id)>,
+    /// Paths present in both indexes, with the old and the new id
+    pub modified: Vec<(PathBuf, Id, Id)>,
+}
+
+impl<Id: ResourceId> IndexDiff<Id> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+    }
+
+    /// Compares relative paths and their ids without touching the
+    /// filesystem, e.g. entries read with [`ResourceIndex::parse_entries`]
+    /// of files which don't exist anymore
+    pub fn between<O, N>(older: O, newer: N) -> Self
+    where
+        O: IntoIterator<Item = (PathBuf, Id)>,
+        N: IntoIterator<Item = (PathBuf, Id)>,
+    {
+        let mut old: HashMap<PathBuf, Id> = older.into_iter().collect();
+        let mut diff = IndexDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            modified: Vec::new(),
+        };
+
+        for (path, id) in newer {
+            match old.remove(&path) {
+                None => diff.added.push((path, id)),
+                Some(old_id) if old_id != id => {
+                    diff.modified.push((path, old_id, id))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed.extend(old);
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.modified.sort();
+        diff
+    }
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Compares this index with a newer one, every list is sorted by path
+    pub fn diff(&self, newer: &ResourceIndex<Id>) -> Result<IndexDiff<Id>> {
+        Ok(IndexDiff::between(
+            self.relative_ids()?,
+            newer.relative_ids()?,
+        ))
+    }
+
+    fn relative_ids(&self) -> Result<HashMap<PathBuf, Id>> {
+        self.path2id
+            .iter()
+            .map(|(path, entry)| {
+                Ok((
+                    self.relative_path(path.as_canonical_path())?,
+                    entry.id.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{
+        create_dir, run_test_and_clean_up, CRC32_1, CRC32_2,
+    };
+    use crate::{IndexDiff, ResourceIndex};
+    use dev_hash::Crc32;
+    use std::path::Path;
+
+    #[test]
+    fn diff_should_compare_different_roots() {
+        run_test_and_clean_up(|path| {
+            let old_dir = create_dir(
+                &path,
+                "old",
+                &[("same.txt", 10), ("gone.txt", 11), ("changed.txt", 10)],
+            );
+            let new_dir = create_dir(
+                &path,
+                "new",
+                &[("same.txt", 10), ("new.txt", 11), ("changed.txt", 11)],
+            );
+
+            let old: ResourceIndex<Crc32> = ResourceIndex::build(&old_dir);
+            let new: ResourceIndex<Crc32> = ResourceIndex::build(&new_dir);
+            let diff = old.diff(&new).expect("Should compare indexes");
+
+            assert_eq!(
+                diff.added,
+                vec![(Path::new("new.txt").into(), CRC32_2)]
+            );
+            assert_eq!(
+                diff.removed,
+                vec![(Path::new("gone.txt").into(), CRC32_2)]
+            );
+            assert_eq!(
+                diff.modified,
+                vec![(Path::new("changed.txt").into(), CRC32_1, CRC32_2)]
+            );
+            assert!(old.diff(&old).unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn stored_entries_should_be_compared_without_their_files() {
+        run_test_and_clean_up(|path| {
+            let dir = create_dir(
+                &path,
+                "root",
+                &[("kept.txt", 10), ("gone.txt", 11)],
+            );
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            index.store().expect("Should store index");
+            std::fs::remove_file(dir.join("gone.txt")).expect("Should remove");
+
+            let stored = ResourceIndex::<Crc32>::load_entries(&dir)
+                .expect("Should read stored entries");
+
+            let diff = IndexDiff::between(
+                stored
+                    .into_iter()
+                    .map(|(path, entry)| (path, entry.id)),
+                vec![(Path::new("kept.txt").into(), CRC32_1)],
+            );
+            assert!(diff.added.is_empty());
+            assert!(diff.modified.is_empty());
+            assert_eq!(
+                diff.removed,
+                vec![(Path::new("gone.txt").into(), CRC32_2)]
+            );
+        })
+    }
+}
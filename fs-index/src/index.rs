@@ -1,19 +1,25 @@
 use anyhow::anyhow;
 use canonical_path::{CanonicalPath, CanonicalPathBuf};
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use fs2::FileExt;
+use rayon::prelude::*;
+use std::collections::{hash_map, HashMap, HashSet};
 use std::fs::{self, File, Metadata};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::ops::Add;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use walkdir::{DirEntry, WalkDir};
 
 use log;
 
 use data_error::{ArklibError, Result};
 use data_resource::ResourceId;
-use fs_storage::{ARK_FOLDER, INDEX_PATH};
+use fs_storage::{ark_folder_path, ark_folder_root, INDEX_PATH};
+
+use crate::arkignore::ArkIgnore;
+use crate::similarity::text_signatures;
+use crate::snapshot::ResourceIndexView;
 
 #[derive(Eq, Ord, PartialEq, PartialOrd, Hash, Clone, Debug)]
 pub struct IndexEntry<Id: ResourceId> {
@@ -21,13 +27,35 @@ pub struct IndexEntry<Id: ResourceId> {
     pub id: Id,
 }
 
-#[derive(PartialEq, Clone, Debug)]
+// the maps are shared with snapshots and clones of the index,
+// they are copied only when modified while shared
+#[derive(Clone, Debug)]
 pub struct ResourceIndex<Id: ResourceId> {
-    pub id2path: HashMap<Id, CanonicalPathBuf>,
-    pub path2id: HashMap<CanonicalPathBuf, IndexEntry<Id>>,
+    pub id2path: Arc<HashMap<Id, CanonicalPathBuf>>,
+    pub path2id: Arc<HashMap<CanonicalPathBuf, IndexEntry<Id>>>,
+
+    pub collisions: Arc<HashMap<Id, usize>>,
+    // paths of the ids in `collisions`, kept up to date
+    // so that `collision_groups` doesn't have to allocate
+    pub(crate) collided_paths: Arc<HashMap<Id, HashSet<CanonicalPathBuf>>>,
+    pub(crate) root: PathBuf,
+    pub(crate) config: IndexConfig,
+    // MinHash signatures of text files computed while scanning, together
+    // with the id they were computed for. They aren't stored, and one is
+    // only valid while its path is still indexed with the same id
+    pub(crate) signatures: Arc<HashMap<CanonicalPathBuf, (Id, Vec<u32>)>>,
+}
 
-    pub collisions: HashMap<Id, usize>,
-    root: PathBuf,
+// signatures are a cache, so an index equals the same index after `load`
+impl<Id: ResourceId> PartialEq for ResourceIndex<Id> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id2path == other.id2path
+            && self.path2id == other.path2id
+            && self.collisions == other.collisions
+            && self.collided_paths == other.collided_paths
+            && self.root == other.root
+            && self.config == other.config
+    }
 }
 
 #[derive(PartialEq, Debug)]
@@ -36,56 +64,582 @@ pub struct IndexUpdate<Id: ResourceId> {
     pub added: HashMap<CanonicalPathBuf, Id>,
 }
 
+impl<Id: ResourceId> Default for IndexUpdate<Id> {
+    fn default() -> Self {
+        IndexUpdate {
+            deleted: HashSet::new(),
+            added: HashMap::new(),
+        }
+    }
+}
+
+impl<Id: ResourceId> IndexUpdate<Id> {
+    /// Total number of changes in the update
+    pub fn len(&self) -> usize {
+        self.added.len() + self.deleted.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Merges a later update into this one, so that accumulated updates
+    /// describe the net change: an id added after being deleted is not
+    /// reported as deleted, and an id added and then deleted again
+    /// cancels out.
+    pub fn extend(&mut self, other: IndexUpdate<Id>) {
+        for id in other.deleted {
+            let added = self.added.len();
+            self.added.retain(|_, added| added != &id);
+            if self.added.len() == added {
+                self.deleted.insert(id);
+            }
+        }
+        for (path, id) in other.added {
+            self.deleted.remove(&id);
+            self.added.insert(path, id);
+        }
+    }
+
+    /// Same as [`IndexUpdate::extend`], but consumes both updates
+    pub fn merge(mut self, other: IndexUpdate<Id>) -> IndexUpdate<Id> {
+        self.extend(other);
+        self
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct IndexConfig {
+    /// Files larger than this are not indexed
+    pub max_file_size_bytes: Option<u64>,
+    /// Extensions of text files compared by
+    /// [`ResourceIndex::find_similar_text_files`]
+    pub shingle_text_extensions: Vec<String>,
+    /// Keeps the index in memory only: [`ResourceIndex::store`] does nothing
+    /// and the `.ark` folder is never created, e.g. for read-only mounts
+    pub no_persist: bool,
+    /// Files discovered after the index reached this size are not indexed,
+    /// [`ResourceIndex::update_all`] keeps updating the existing entries
+    pub max_entries: Option<usize>,
+    /// Decides which entries are indexed by [`ResourceIndex::build`] and
+    /// [`ResourceIndex::update_all`], [`should_index`] is used if not set
+    pub selector: Option<Selector>,
+}
+
+/// Replaces [`should_index`], see [`ResourceIndex::build_with_selector`]
+#[derive(Clone)]
+pub struct Selector(Arc<dyn Fn(&DirEntry) -> bool + Send + Sync>);
+
+impl Selector {
+    pub fn new<F>(selector: F) -> Self
+    where
+        F: Fn(&DirEntry) -> bool + Send + Sync + 'static,
+    {
+        Selector(Arc::new(selector))
+    }
+
+    pub fn matches(&self, entry: &DirEntry) -> bool {
+        (self.0)(entry)
+    }
+}
+
+// closures can't be compared, so only clones of the same selector are equal
+impl PartialEq for Selector {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Selector {}
+
+impl std::fmt::Debug for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Selector").finish_non_exhaustive()
+    }
+}
+
+impl IndexConfig {
+    fn should_index(&self, entry: &DirEntry) -> bool {
+        match &self.selector {
+            Some(selector) => selector.matches(entry),
+            None => should_index(entry),
+        }
+    }
+}
+
 pub const RESOURCE_UPDATED_THRESHOLD: Duration = Duration::from_millis(1);
 
+// version of the format written by `ResourceIndex::store`
+pub const INDEX_VERSION: u32 = 2;
+
+// first line of the stored index, followed by the format version
+const VERSION_HEADER: &str = "version ";
+// second line of the stored index, followed by the hash algorithm name
+const ALGORITHM_HEADER: &str = "algorithm ";
+
+// most filesystems limit names of files and directories to 255 bytes,
+// even when they support much longer paths
+pub const MAX_PATH_COMPONENT_LEN: usize = 255;
+
+// number of files hashed in parallel by `scan_entries` at a time
+const SCAN_CHUNK_SIZE: usize = 1024;
+
+const INDEX_TMP_EXTENSION: &str = "tmp";
+const INDEX_LOCK_EXTENSION: &str = "lock";
+
 pub type Paths = HashSet<CanonicalPathBuf>;
 
+pub type EntryRef<'a, Id> = (&'a CanonicalPathBuf, &'a IndexEntry<Id>);
+
+pub type CollisionGroups<'a, Id> = HashMap<Id, Vec<EntryRef<'a, Id>>>;
+
 impl<Id: ResourceId> ResourceIndex<Id> {
+    // the read-only queries are implemented by `ResourceIndexView`,
+    // they are repeated here so that callers don't need to import it
+
     pub fn size(&self) -> usize {
-        //the actual size is lower in presence of collisions
-        self.path2id.len()
+        ResourceIndexView::size(self)
+    }
+
+    pub fn len_unique(&self) -> usize {
+        ResourceIndexView::len_unique(self)
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, CanonicalPathBuf, IndexEntry<Id>> {
+        self.path2id.iter()
+    }
+
+    // removes all entries, yielding them without cloning
+    // unless the maps are shared with a snapshot or a clone
+    pub fn drain(
+        &mut self,
+    ) -> hash_map::IntoIter<CanonicalPathBuf, IndexEntry<Id>> {
+        Arc::make_mut(&mut self.id2path).clear();
+        Arc::make_mut(&mut self.collisions).clear();
+        Arc::make_mut(&mut self.collided_paths).clear();
+
+        let path2id = std::mem::take(&mut self.path2id);
+        Arc::try_unwrap(path2id)
+            .unwrap_or_else(|shared| (*shared).clone())
+            .into_iter()
+    }
+
+    // plain mapping from absolute paths to ids
+    pub fn to_hashmap(&self) -> HashMap<PathBuf, Id> {
+        self.path2id
+            .iter()
+            .map(|(path, entry)| {
+                (path.as_path().to_path_buf(), entry.id.clone())
+            })
+            .collect()
+    }
+
+    pub fn get_resource_by_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<&IndexEntry<Id>> {
+        ResourceIndexView::get_resource_by_path(self, path)
+    }
+
+    // same as `get_resource_by_path`, but fails with
+    // `ResourceNotFound` when the path isn't indexed
+    pub fn get_resource_by_path_or_error<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<&IndexEntry<Id>> {
+        self.get_resource_by_path(&path)
+            .ok_or_else(|| ArklibError::ResourceNotFound(path.as_ref().into()))
+    }
+
+    pub fn contains_path(&self, path: &Path) -> bool {
+        ResourceIndexView::contains_path(self, path)
+    }
+
+    pub fn contains_id(&self, id: &Id) -> bool {
+        ResourceIndexView::contains_id(self, id)
+    }
+
+    pub fn get_resource_by_id_any(&self, id: &Id) -> Option<EntryRef<'_, Id>> {
+        ResourceIndexView::get_resource_by_id_any(self, id)
+    }
+
+    // every path with the given id without allocating: the set of
+    // a collided id is borrowed from `collision_groups`, otherwise
+    // the single path is borrowed from `id2path`
+    pub fn get_paths_by_id(
+        &self,
+        id: &Id,
+    ) -> Option<impl Iterator<Item = &CanonicalPathBuf> + '_> {
+        let path = self.id2path.get(id)?;
+        Some(match self.collided_paths.get(id) {
+            Some(paths) => itertools::Either::Left(paths.iter()),
+            None => itertools::Either::Right(std::iter::once(path)),
+        })
     }
 
+    // hashes `bytes` and looks up every path with the same id,
+    // `None` means that the content is not indexed
+    pub fn get_resource_by_content(
+        &self,
+        bytes: &[u8],
+    ) -> Result<Option<Vec<EntryRef<'_, Id>>>> {
+        let id = Id::from_bytes(bytes)?;
+        let path = match self.id2path.get(&id) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        if !self.collisions.contains_key(&id) {
+            return Ok(self.path2id.get_key_value(path).map(|e| vec![e]));
+        }
+        Ok(Some(
+            self.path2id
+                .iter()
+                .filter(|(_, entry)| entry.id == id)
+                .collect(),
+        ))
+    }
+}
+
+// files are hashed on the rayon thread pool,
+// so ids have to be sent between threads
+impl<Id: ResourceId + Send + Sync> ResourceIndex<Id> {
     pub fn build<P: AsRef<Path>>(root_path: P) -> Self {
-        log::info!("Building the index from scratch");
+        Self::build_with_config(root_path, IndexConfig::default())
+    }
+
+    pub fn build_with_config<P: AsRef<Path>>(
+        root_path: P,
+        config: IndexConfig,
+    ) -> Self {
+        Self::build_walking(root_path.as_ref(), config, false)
+            .expect("Unreadable files are skipped")
+    }
+
+    // same as `build_with_config`, but returns an error instead
+    // of skipping files which exist but can't be read
+    pub fn build_strict<P: AsRef<Path>>(
+        root_path: P,
+        config: IndexConfig,
+    ) -> Result<Self> {
+        Self::build_walking(root_path.as_ref(), config, true)
+    }
+
+    fn build_walking(
+        root_path: &Path,
+        config: IndexConfig,
+        strict: bool,
+    ) -> Result<Self> {
+        // the walk is lazy, so it needs its own copy of the config
+        let walk_config = config.clone();
+        let entries = discover_paths(root_path, move |entry| {
+            walk_config.should_index(entry)
+        });
+        Self::build_scanning(root_path, config, entries, strict)
+    }
+
+    // hashes the discovered entries in parallel,
+    // shared by every way of discovering them
+    fn build_scanning(
+        root_path: &Path,
+        config: IndexConfig,
+        entries: impl Iterator<Item = (CanonicalPathBuf, DirEntry)>,
+        strict: bool,
+    ) -> Result<Self> {
+        let root_path: PathBuf = root_path.to_owned();
+        let _span = tracing::info_span!(
+            "ResourceIndex::build",
+            root = %root_path.display()
+        )
+        .entered();
+
+        tracing::info!("Building the index from scratch");
+        let start_time = Instant::now();
+
+        let (entries, skipped) =
+            scan_entries(entries, &config, config.max_entries, strict)?;
+        skipped.report();
+        for path in entries.keys() {
+            warn_long_components(path.as_path());
+        }
+
+        let signatures = text_signatures(&entries, &config);
+        let mut index = Self::from_entries(root_path, config, entries);
+        index.signatures = Arc::new(signatures);
+
+        tracing::info!(
+            files = index.size(),
+            unique_ids = index.len_unique(),
+            collisions = index.collisions.len(),
+            excluded_bytes = skipped.excluded_bytes,
+            elapsed = ?start_time.elapsed(),
+            "Built index"
+        );
+        Ok(index)
+    }
+
+    /// Same as [`ResourceIndex::build`], but `selector` decides which
+    /// entries are indexed instead of [`should_index`]. Directories are
+    /// passed to it too, including the root, and rejected directories
+    /// aren't walked. The `.ark` folder, `.arkignore` rules and directory
+    /// cycles are handled regardless of the selector. It is kept in
+    /// [`IndexConfig::selector`], so [`ResourceIndex::update_all`] uses it too.
+    pub fn build_with_selector<P, F>(root_path: P, selector: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: Fn(&DirEntry) -> bool + Send + Sync + 'static,
+    {
+        let config = IndexConfig {
+            selector: Some(Selector::new(selector)),
+            ..Default::default()
+        };
+        Self::build_walking(root_path.as_ref(), config, false)
+    }
+
+    // `discover` receives the root and returns absolute paths to index,
+    // which replaces walking the root; the paths are hashed the same way
+    // as in `build`, and those which can't be read are skipped with a warning
+    pub fn build_with_discovery<P, F>(root_path: P, discover: F) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        F: Fn(&Path) -> Result<Vec<PathBuf>>,
+    {
+        let root_path = root_path.as_ref();
+        let entries = discover(root_path)?
+            .into_iter()
+            .filter_map(|path| match discovered_entry(&path) {
+                Ok(entry) => Some(entry),
+                Err(msg) => {
+                    tracing::warn!(
+                        "Skipping {}, couldn't read it:\n{}",
+                        path.display(),
+                        msg
+                    );
+                    None
+                }
+            });
+        Self::build_scanning(root_path, IndexConfig::default(), entries, false)
+    }
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    // builds an index from entries hashed elsewhere, e.g. read from
+    // another format; paths can be either absolute or relative
+    // to `root_path`, every one of them must exist
+    pub fn build_from_iter<P, I>(root_path: P, iter: I) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (PathBuf, IndexEntry<Id>)>,
+    {
+        let root_path: PathBuf = root_path.as_ref().to_owned();
+        let entries = resolve_entries(&root_path, iter, false)?;
+
+        Ok(Self::from_entries(
+            root_path,
+            IndexConfig::default(),
+            entries,
+        ))
+    }
+
+    /// Builds an index from known ids without hashing any file, e.g. from
+    /// a format without timestamps. Paths are relative to `root_path` or
+    /// absolute, the ones which don't exist are skipped. Timestamps are
+    /// set to the Unix epoch, so the next `update_all` rehashes every file.
+    ///
+    /// Unlike [`ResourceIndex::from_manifest`], which parses a manifest
+    /// file and fails on missing paths, this takes the ids as a map.
+    pub fn build_from_manifest<P: AsRef<Path>>(
+        root_path: P,
+        manifest: HashMap<PathBuf, Id>,
+    ) -> Self {
         let root_path: PathBuf = root_path.as_ref().to_owned();
 
-        let entries = discover_paths(&root_path);
-        let entries = scan_entries(entries);
+        let entries = manifest.into_iter().map(|(path, id)| {
+            let entry = IndexEntry {
+                modified: UNIX_EPOCH,
+                id,
+            };
+            (path, entry)
+        });
+        let entries = resolve_entries(&root_path, entries, true)
+            .expect("Missing paths are skipped");
+
+        Self::from_entries(root_path, IndexConfig::default(), entries)
+    }
+
+    /// Builds an index from already hashed resources,
+    /// nothing is read from disk
+    pub fn from_resources<I>(root_path: PathBuf, iter: I) -> Self
+    where
+        I: IntoIterator<Item = (CanonicalPathBuf, IndexEntry<Id>)>,
+    {
+        Self::from_entries(root_path, IndexConfig::default(), iter)
+    }
+
+    pub(crate) fn from_entries<I>(
+        root_path: PathBuf,
+        config: IndexConfig,
+        entries: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = (CanonicalPathBuf, IndexEntry<Id>)>,
+    {
+        // the number of entries is usually known after discovery,
+        // so the maps don't need to grow while being populated
+        let entries = entries.into_iter();
+        let capacity = entries.size_hint().0;
 
         let mut index = ResourceIndex {
-            id2path: HashMap::new(),
-            path2id: HashMap::new(),
-            collisions: HashMap::new(),
-            root: root_path,
+            id2path: Arc::new(HashMap::with_capacity(capacity)),
+            path2id: Arc::new(HashMap::with_capacity(capacity)),
+            collisions: Arc::new(HashMap::new()),
+            collided_paths: Arc::new(HashMap::new()),
+            root: canonicalize_root(root_path),
+            config,
+            signatures: Arc::new(HashMap::new()),
         };
 
         for (path, entry) in entries {
             index.insert_entry(path, entry);
         }
 
-        log::info!("Index built");
         index
     }
 
     pub fn load<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        Self::load_with_config(root_path, IndexConfig::default())
+    }
+
+    // the config isn't stored together with the index,
+    // so it has to be given again to be used by `update_all`
+    pub fn load_with_config<P: AsRef<Path>>(
+        root_path: P,
+        config: IndexConfig,
+    ) -> Result<Self> {
         let root_path: PathBuf = root_path.as_ref().to_owned();
 
-        let index_path: PathBuf = root_path.join(ARK_FOLDER).join(INDEX_PATH);
+        let index_path: PathBuf = ark_folder_path(&root_path).join(INDEX_PATH);
+        let mut index = Self::read_index_file(root_path, &index_path)?;
+        index.config = config;
+        Ok(index)
+    }
+
+    // `index_path` must point to an index file stored
+    // inside of `.ark` folder of the indexed root; under an absolute
+    // `ARK_FOLDER` the root can't be found, `load` has to be used instead
+    pub fn import_from_index_file<P: AsRef<Path>>(
+        index_path: P,
+    ) -> Result<Self> {
+        let index_path = index_path.as_ref();
+        let root_path = index_path
+            .parent()
+            .and_then(ark_folder_root)
+            .ok_or_else(|| {
+                ArklibError::Path(format!(
+                    "{} is not inside of the ark folder of its root",
+                    index_path.display()
+                ))
+            })?;
+
+        Self::read_index_file(root_path, index_path)
+    }
+
+    fn read_index_file(root_path: PathBuf, index_path: &Path) -> Result<Self> {
         log::info!("Loading the index from file {}", index_path.display());
-        let file = File::open(&index_path)?;
+        let file = File::open(index_path)?;
+        let _lock = lock_index_file_shared(index_path)?;
+
+        Self::load_from_reader(root_path, file)
+    }
+
+    // parses the index in the format written by `store`,
+    // paths are resolved against `root_path`
+    pub fn load_from_reader<P: AsRef<Path>, R: Read>(
+        root_path: P,
+        reader: R,
+    ) -> Result<Self> {
+        let root_path: PathBuf = root_path.as_ref().to_owned();
         let mut index = ResourceIndex {
-            id2path: HashMap::new(),
-            path2id: HashMap::new(),
-            collisions: HashMap::new(),
-            root: root_path.clone(),
+            id2path: Arc::new(HashMap::new()),
+            path2id: Arc::new(HashMap::new()),
+            collisions: Arc::new(HashMap::new()),
+            collided_paths: Arc::new(HashMap::new()),
+            root: canonicalize_root(root_path.clone()),
+            config: IndexConfig::default(),
+            signatures: Arc::new(HashMap::new()),
         };
 
         // We should not return early in case of missing files
-        let lines = BufReader::new(file).lines();
+        for (path, entry) in Self::parse_entries(reader)? {
+            let path: PathBuf = root_path.join(path);
+            match CanonicalPathBuf::canonicalize(&path) {
+                Ok(path) => {
+                    log::trace!("[load] {} -> {}", entry.id, path.display());
+                    index.insert_entry(path, entry);
+                }
+                Err(_) => {
+                    log::warn!("File {} not found", path.display());
+                    continue;
+                }
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Reads the entries of the stored index as they are, including
+    /// the ones whose files don't exist anymore, see
+    /// [`ResourceIndex::parse_entries`]
+    pub fn load_entries<P: AsRef<Path>>(
+        root_path: P,
+    ) -> Result<Vec<(PathBuf, IndexEntry<Id>)>> {
+        let index_path = ark_folder_path(root_path.as_ref()).join(INDEX_PATH);
+        let file = File::open(&index_path)?;
+        let _lock = lock_index_file_shared(&index_path)?;
+
+        Self::parse_entries(file)
+    }
+
+    /// Parses the index in the format written by [`ResourceIndex::store`]
+    /// without touching the filesystem, so paths stay relative to
+    /// the indexed root and may point to files which are gone
+    pub fn parse_entries<R: Read>(
+        reader: R,
+    ) -> Result<Vec<(PathBuf, IndexEntry<Id>)>> {
+        let mut lines = BufReader::new(reader).lines().peekable();
+
+        // version 1 has no header at all
+        let version = match lines.peek() {
+            Some(Ok(line)) if line.starts_with(VERSION_HEADER) => {
+                let version = lines.next().unwrap()?;
+                version[VERSION_HEADER.len()..]
+                    .parse()
+                    .map_err(|_| ArklibError::Parse)?
+            }
+            _ => 1,
+        };
+        log::debug!("Index format version {}", version);
+
+        match version {
+            1 => {}
+            2 => {
+                let line = lines.next().ok_or(ArklibError::Parse)??;
+                let algorithm = line
+                    .strip_prefix(ALGORITHM_HEADER)
+                    .ok_or(ArklibError::Parse)?;
+                check_algorithm::<Id>(algorithm)?;
+            }
+            _ => {
+                return Err(ArklibError::Other(anyhow!(
+                    "Unsupported index version {}",
+                    version
+                )))
+            }
+        }
+
+        let mut entries = Vec::new();
         for line in lines {
             let line = line?;
-
             let mut parts = line.split(' ');
 
             let modified = {
@@ -102,41 +656,67 @@ impl<Id: ResourceId> ResourceIndex<Id> {
 
             let path: String =
                 itertools::Itertools::intersperse(parts, " ").collect();
-            let path: PathBuf = root_path.join(Path::new(&path));
-            match CanonicalPathBuf::canonicalize(&path) {
-                Ok(path) => {
-                    log::trace!("[load] {} -> {}", id, path.display());
-                    index.insert_entry(path, IndexEntry { modified, id });
-                }
-                Err(_) => {
-                    log::warn!("File {} not found", path.display());
-                    continue;
-                }
-            }
+            entries.push((PathBuf::from(path), IndexEntry { modified, id }));
         }
 
-        Ok(index)
+        Ok(entries)
     }
 
+    // holds an exclusive lock on `.ark/index.lock` while writing,
+    // so that concurrent writers can't interleave
     pub fn store(&self) -> Result<()> {
+        if self.config.no_persist {
+            log::debug!("Persistence is disabled, not storing the index");
+            return Ok(());
+        }
+
         log::info!("Storing the index to file");
 
         let start = SystemTime::now();
 
-        let index_path = self
-            .root
-            .to_owned()
-            .join(ARK_FOLDER)
-            .join(INDEX_PATH);
+        let index_path = ark_folder_path(&self.root).join(INDEX_PATH);
 
         let ark_dir = index_path.parent().unwrap();
         fs::create_dir_all(ark_dir)?;
 
-        let mut file = File::create(index_path)?;
+        let _lock = lock_index_file(&index_path)?;
+
+        // the index is written into a temporary file first and then renamed,
+        // so that a crash never leaves a truncated index behind
+        let tmp_path = index_path.with_extension(INDEX_TMP_EXTENSION);
+        let mut file = File::create(&tmp_path)?;
+
+        self.write_index(&mut file)?;
+
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp_path, &index_path)?;
+
+        log::info!(
+            "Stored index: {} bytes ({} entries)",
+            fs::metadata(&index_path)?.len(),
+            self.size()
+        );
+
+        log::trace!(
+            "Storing the index took {:?}",
+            start
+                .elapsed()
+                .map_err(|_| ArklibError::Other(anyhow!("SystemTime error")))
+        );
+        Ok(())
+    }
 
+    // the format read by `load_from_reader`
+    fn write_index<W: Write>(&self, writer: &mut W) -> Result<()> {
         let mut path2id: Vec<(&CanonicalPathBuf, &IndexEntry<Id>)> =
             self.path2id.iter().collect();
-        path2id.sort_by_key(|(_, entry)| *entry);
+        // paths break ties between equal entries, e.g. duplicates
+        // with the same timestamp, so that the output is reproducible
+        path2id.sort_by_key(|(path, entry)| (*entry, *path));
+
+        writeln!(writer, "{}{}", VERSION_HEADER, INDEX_VERSION)?;
+        writeln!(writer, "{}{}", ALGORITHM_HEADER, algorithm_name::<Id>())?;
 
         for (path, entry) in path2id.iter() {
             log::trace!("[store] {} by path {}", entry.id, path.display());
@@ -149,67 +729,128 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                 })?
                 .as_millis();
 
-            let path =
-                pathdiff::diff_paths(path.to_str().unwrap(), self.root.clone())
-                    .ok_or(ArklibError::Path(
-                        "Couldn't calculate path diff".into(),
-                    ))?;
+            let path = self.relative_path(path.as_canonical_path())?;
 
-            writeln!(file, "{} {} {}", timestamp, entry.id, path.display())?;
+            writeln!(writer, "{} {} {}", timestamp, entry.id, path.display())?;
         }
+        Ok(())
+    }
 
-        log::trace!(
-            "Storing the index took {:?}",
-            start
-                .elapsed()
-                .map_err(|_| ArklibError::Other(anyhow!("SystemTime error")))
+    /// Same as [`ResourceIndex::store`], but writes the file
+    /// without blocking the async runtime
+    #[cfg(feature = "tokio")]
+    pub async fn store_async(&self) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        if self.config.no_persist {
+            log::debug!("Persistence is disabled, not storing the index");
+            return Ok(());
+        }
+
+        log::info!("Storing the index to file");
+
+        let index_path = ark_folder_path(&self.root).join(INDEX_PATH);
+        tokio::fs::create_dir_all(index_path.parent().unwrap()).await?;
+
+        // waiting for the lock blocks, so it's done off the runtime
+        let lock_path = index_path.clone();
+        let _lock =
+            tokio::task::spawn_blocking(move || lock_index_file(&lock_path))
+                .await
+                .map_err(|e| ArklibError::Other(e.into()))??;
+
+        let mut content = Vec::new();
+        self.write_index(&mut content)?;
+
+        let tmp_path = index_path.with_extension(INDEX_TMP_EXTENSION);
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        file.write_all(&content).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp_path, &index_path).await?;
+
+        log::info!(
+            "Stored index: {} bytes ({} entries)",
+            content.len(),
+            self.size()
         );
         Ok(())
     }
+}
 
-    pub fn provide<P: AsRef<Path>>(root_path: P) -> Result<Self> {
-        match Self::load(&root_path) {
+impl<Id: ResourceId + Send + Sync> ResourceIndex<Id> {
+    /// Uses the stored index as a baseline, so that only new files and
+    /// files modified since it was stored are hashed. Falls back to
+    /// a full build when no usable index is stored. Fails if the stored
+    /// index can't be updated, instead of returning outdated entries.
+    /// Nothing is stored.
+    pub fn build_incremental<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        Self::build_incremental_with_config(root_path, IndexConfig::default())
+    }
+
+    /// Same as [`ResourceIndex::build_incremental`], but `config` is used
+    /// both for the loaded index and for the full build
+    pub fn build_incremental_with_config<P: AsRef<Path>>(
+        root_path: P,
+        config: IndexConfig,
+    ) -> Result<Self> {
+        match Self::load_with_config(&root_path, config.clone()) {
             Ok(mut index) => {
                 log::debug!("Index loaded: {} entries", index.path2id.len());
 
-                match index.update_all() {
-                    Ok(update) => {
-                        log::debug!(
-                            "Index updated: {} added, {} deleted",
-                            update.added.len(),
-                            update.deleted.len()
-                        );
-                    }
-                    Err(e) => {
-                        log::error!(
-                            "Failed to update index: {}",
-                            e.to_string()
-                        );
-                    }
-                }
-
-                if let Err(e) = index.store() {
-                    log::error!("{}", e.to_string());
-                }
+                // the loaded entries are outdated if the update fails
+                let update = index.update_all()?;
+                log::debug!(
+                    "Index updated: {} added, {} deleted",
+                    update.added.len(),
+                    update.deleted.len()
+                );
                 Ok(index)
             }
             Err(e) => {
-                log::warn!("{}", e.to_string());
-                Ok(Self::build(root_path))
+                log::warn!("Couldn't load the stored index: {}", e);
+                Ok(Self::build_with_config(root_path, config))
             }
         }
     }
 
-    pub fn update_all(&mut self) -> Result<IndexUpdate<Id>> {
-        log::debug!("Updating the index");
-        log::trace!("[update] known paths: {:?}", self.path2id.keys());
+    pub fn provide<P: AsRef<Path>>(root_path: P) -> Result<Self> {
+        Self::provide_with_config(root_path, IndexConfig::default())
+    }
+
+    // same as `build_incremental_with_config`, but the result is stored
+    pub fn provide_with_config<P: AsRef<Path>>(
+        root_path: P,
+        config: IndexConfig,
+    ) -> Result<Self> {
+        let index = Self::build_incremental_with_config(root_path, config)?;
+        if let Err(e) = index.store() {
+            log::error!("{}", e.to_string());
+        }
+        Ok(index)
+    }
 
-        let curr_entries = discover_paths(self.root.clone());
+    pub fn update_all(&mut self) -> Result<IndexUpdate<Id>> {
+        let _span = tracing::info_span!(
+            "ResourceIndex::update_all",
+            root = %self.root.display()
+        )
+        .entered();
+
+        tracing::debug!("Updating the index");
+        tracing::trace!("[update] known paths: {:?}", self.path2id.keys());
+
+        let curr_entries: HashMap<CanonicalPathBuf, DirEntry> =
+            discover_paths(self.root.clone(), |entry| {
+                self.config.should_index(entry)
+            })
+            .collect();
 
         //assuming that collections manipulation is
         // quicker than asking `path.exists()` for every path
         let curr_paths: Paths = curr_entries.keys().cloned().collect();
         let prev_paths: Paths = self.path2id.keys().cloned().collect();
+
         let preserved_paths: Paths = curr_paths
             .intersection(&prev_paths)
             .cloned()
@@ -226,7 +867,7 @@ impl<Id: ResourceId> ResourceIndex<Id> {
             })
             .collect();
 
-        log::debug!("Checking updated paths");
+        tracing::debug!("Checking updated paths");
         let updated_paths: HashMap<CanonicalPathBuf, DirEntry> = curr_entries
             .into_iter()
             .filter(|(path, dir_entry)| {
@@ -239,7 +880,7 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                     let result = dir_entry.metadata();
                     match result {
                         Err(msg) => {
-                            log::error!(
+                            tracing::error!(
                                 "Couldn't retrieve metadata for {}: {}",
                                 &path.display(),
                                 msg
@@ -248,7 +889,7 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                         }
                         Ok(metadata) => match metadata.modified() {
                             Err(msg) => {
-                                log::error!(
+                                tracing::error!(
                                     "Couldn't retrieve timestamp for {}: {}",
                                     &path.display(),
                                     msg
@@ -256,14 +897,25 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                                 false
                             }
                             Ok(curr_modified) => {
-                                let elapsed = curr_modified
+                                // the clock could have gone backward
+                                let elapsed = match curr_modified
                                     .duration_since(prev_modified)
-                                    .unwrap();
+                                {
+                                    Ok(elapsed) => elapsed,
+                                    Err(_) => {
+                                        tracing::warn!(
+                                            "Timestamp of {} went backward, \
+                                            treating it as not updated",
+                                            path.display()
+                                        );
+                                        return false;
+                                    }
+                                };
 
                                 let was_updated =
                                     elapsed >= RESOURCE_UPDATED_THRESHOLD;
                                 if was_updated {
-                                    log::trace!(
+                                    tracing::trace!(
                                         "[update] modified {} by path {}
                                         \twas {:?}
                                         \tnow {:?}
@@ -284,49 +936,45 @@ impl<Id: ResourceId> ResourceIndex<Id> {
             })
             .collect();
 
-        let mut deleted: HashSet<Id> = HashSet::new();
-
         // treating both deleted and updated paths as deletions
-        prev_paths
+        let removed_paths: Vec<CanonicalPathBuf> = prev_paths
             .difference(&preserved_paths)
             .cloned()
             .chain(updated_paths.keys().cloned())
-            .for_each(|path| {
-                if let Some(entry) =
-                    self.path2id.remove(path.as_canonical_path())
-                {
-                    let k = self.collisions.remove(&entry.id).unwrap_or(1);
-                    if k > 1 {
-                        self.collisions.insert(entry.id, k - 1);
-                    } else {
-                        log::trace!(
-                            "[delete] {} by path {}",
-                            entry.id,
-                            path.display()
-                        );
-                        self.id2path.remove(&entry.id);
-                        deleted.insert(entry.id);
-                    }
-                } else {
-                    log::warn!("Path {} was not known", path.display());
-                }
-            });
+            .collect();
 
-        let added: HashMap<CanonicalPathBuf, IndexEntry<Id>> =
-            scan_entries(updated_paths)
-                .into_iter()
-                .chain({
-                    log::debug!("Checking added paths");
-                    scan_entries(created_paths).into_iter()
-                })
-                .filter(|(_, entry)| !self.id2path.contains_key(&entry.id))
-                .collect();
+        // updated paths are indexed again regardless of the limit,
+        // only created paths are dropped when the index is full
+        let kept = prev_paths.len() - removed_paths.len() + updated_paths.len();
+        let created_limit = self
+            .config
+            .max_entries
+            .map(|max_entries| max_entries.saturating_sub(kept));
+
+        let deleted = self.remove_paths(removed_paths);
+
+        let (updated, skipped) =
+            scan_entries(updated_paths, &self.config, None, false)?;
+        tracing::debug!("Checking added paths");
+        let (created, skipped_created) =
+            scan_entries(created_paths, &self.config, created_limit, false)?;
+        skipped.merge(skipped_created).report();
+        // only new paths, so that every update doesn't repeat the warning
+        for path in created.keys() {
+            warn_long_components(path.as_path());
+        }
+
+        let added: HashMap<CanonicalPathBuf, IndexEntry<Id>> = updated
+            .into_iter()
+            .chain(created)
+            .filter(|(_, entry)| !self.id2path.contains_key(&entry.id))
+            .collect();
 
         for (path, entry) in added.iter() {
             if deleted.contains(&entry.id) {
                 // emitting the resource as both deleted and added
                 // (renaming a duplicate might remain undetected)
-                log::trace!(
+                tracing::trace!(
                     "[update] moved {} to path {}",
                     entry.id,
                     path.display()
@@ -336,6 +984,18 @@ impl<Id: ResourceId> ResourceIndex<Id> {
             self.insert_entry(path.clone(), entry.clone());
         }
 
+        let signatures = text_signatures(&added, &self.config);
+        if !signatures.is_empty() || !self.signatures.is_empty() {
+            let path2id = &self.path2id;
+            let known = Arc::make_mut(&mut self.signatures);
+            known.retain(|path, (id, _)| {
+                path2id
+                    .get(path)
+                    .is_some_and(|entry| &entry.id == id)
+            });
+            known.extend(signatures);
+        }
+
         let added: HashMap<CanonicalPathBuf, Id> = added
             .into_iter()
             .map(|(path, entry)| (path, entry.id))
@@ -343,7 +1003,9 @@ impl<Id: ResourceId> ResourceIndex<Id> {
 
         Ok(IndexUpdate { deleted, added })
     }
+}
 
+impl<Id: ResourceId> ResourceIndex<Id> {
     // the caller must ensure that:
     // * the index is up-to-date except this single path
     // * the path hasn't been indexed before
@@ -368,24 +1030,17 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                     "Couldn't to retrieve file metadata".into(),
                 ));
             }
-            Ok(metadata) => match scan_entry(path, metadata) {
+            Ok(metadata) => match scan_entry::<Id>(path, metadata) {
                 Err(_) => {
                     return Err(ArklibError::Path(
                         "The path points to a directory or empty file".into(),
                     ));
                 }
                 Ok(new_entry) => {
-                    let id = new_entry.clone().id;
-
-                    if let Some(nonempty) = self.collisions.get_mut(&id) {
-                        *nonempty += 1;
-                    }
-
                     let mut added = HashMap::new();
-                    added.insert(path_buf.clone(), id.clone());
+                    added.insert(path_buf.clone(), new_entry.id.clone());
 
-                    self.id2path.insert(id, path_buf.clone());
-                    self.path2id.insert(path_buf, new_entry);
+                    self.insert_entry(path_buf, new_entry);
 
                     Ok(IndexUpdate {
                         added,
@@ -441,8 +1096,8 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                             // if the path is not indexed, then we can't have
                             // `old_id` if you want
                             // to index new path, use `index_new` method
-                            return Err(ArklibError::Path(
-                                "Couldn't find the path in the index".into(),
+                            return Err(ArklibError::ResourceNotFound(
+                                path_buf.into_path_buf(),
                             ));
                         }
                         let curr_entry = curr_entry.unwrap();
@@ -477,46 +1132,225 @@ impl<Id: ResourceId> ResourceIndex<Id> {
         };
     }
 
-    pub fn forget_id(&mut self, old_id: Id) -> Result<IndexUpdate<Id>> {
-        let old_path = self
-            .path2id
-            .drain()
-            .filter_map(|(k, v)| {
-                if v.id == old_id {
-                    Some(k)
-                } else {
-                    None
-                }
-            })
-            .collect_vec();
-        for p in old_path {
-            self.path2id.remove(&p);
+    // keeps only the entries for which `predicate` returns `true`,
+    // `id2path` and `collisions` are adjusted to the remaining entries
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&CanonicalPath, &IndexEntry<Id>) -> bool,
+    {
+        Arc::make_mut(&mut self.path2id)
+            .retain(|path, entry| predicate(path.as_canonical_path(), entry));
+
+        let mut counts: HashMap<Id, usize> = HashMap::new();
+        for entry in self.path2id.values() {
+            *counts.entry(entry.id.clone()).or_insert(0) += 1;
         }
-        self.id2path.remove(&old_id);
-        let mut deleted = HashSet::new();
-        deleted.insert(old_id);
 
-        Ok(IndexUpdate {
-            added: HashMap::new(),
-            deleted,
-        })
-    }
+        Arc::make_mut(&mut self.id2path).retain(|id, path| {
+            counts.contains_key(id) && self.path2id.contains_key(path)
+        });
+        for (path, entry) in self.path2id.iter() {
+            if !self.id2path.contains_key(&entry.id) {
+                Arc::make_mut(&mut self.id2path)
+                    .insert(entry.id.clone(), path.clone());
+            }
+        }
 
-    fn insert_entry(&mut self, path: CanonicalPathBuf, entry: IndexEntry<Id>) {
-        log::trace!("[add] {} by path {}", entry.id, path.display());
+        self.collisions = Arc::new(
+            counts
+                .into_iter()
+                .filter(|(_, count)| *count > 1)
+                .collect(),
+        );
+
+        let mut collided_paths: HashMap<Id, HashSet<CanonicalPathBuf>> =
+            HashMap::new();
+        for (path, entry) in self.path2id.iter() {
+            if self.collisions.contains_key(&entry.id) {
+                collided_paths
+                    .entry(entry.id.clone())
+                    .or_default()
+                    .insert(path.clone());
+            }
+        }
+        self.collided_paths = Arc::new(collided_paths);
+    }
+
+    pub fn collision_groups(
+        &self,
+    ) -> hash_map::Iter<'_, Id, HashSet<CanonicalPathBuf>> {
+        ResourceIndexView::collision_groups(self)
+    }
+
+    // groups of paths which are hardlinks to the same file, sorted;
+    // hardlinks always have the same id, so only collisions are checked
+    #[cfg(target_family = "unix")]
+    pub fn get_hardlink_groups(&self) -> Vec<Vec<PathBuf>> {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut groups: HashMap<(u64, u64), Vec<PathBuf>> = HashMap::new();
+        for (path, entry) in self.path2id.iter() {
+            if !self.collisions.contains_key(&entry.id) {
+                continue;
+            }
+            match fs::metadata(path) {
+                Ok(metadata) => groups
+                    .entry((metadata.dev(), metadata.ino()))
+                    .or_default()
+                    .push(path.clone().into_path_buf()),
+                Err(err) => log::warn!(
+                    "Couldn't retrieve metadata for {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+
+        let mut groups: Vec<Vec<PathBuf>> = groups
+            .into_values()
+            .filter(|paths| paths.len() > 1)
+            .map(|mut paths| {
+                paths.sort();
+                paths
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    // splits the entries into ones with a unique id
+    // and groups of entries sharing the same id
+    pub fn partition_by_collision(
+        &self,
+    ) -> (Vec<EntryRef<'_, Id>>, CollisionGroups<'_, Id>) {
+        let mut unique = Vec::new();
+        let mut collided: CollisionGroups<'_, Id> = HashMap::new();
+
+        for (path, entry) in self.path2id.iter() {
+            if self.collisions.contains_key(&entry.id) {
+                collided
+                    .entry(entry.id.clone())
+                    .or_default()
+                    .push((path, entry));
+            } else {
+                unique.push((path, entry));
+            }
+        }
+
+        (unique, collided)
+    }
+
+    pub fn forget_id(&mut self, old_id: Id) -> Result<IndexUpdate<Id>> {
+        Arc::make_mut(&mut self.path2id).retain(|_, entry| entry.id != old_id);
+        Arc::make_mut(&mut self.id2path).remove(&old_id);
+        // every path of the id is gone, so it can't collide anymore
+        Arc::make_mut(&mut self.collisions).remove(&old_id);
+        Arc::make_mut(&mut self.collided_paths).remove(&old_id);
+        let mut deleted = HashSet::new();
+        deleted.insert(old_id);
+
+        Ok(IndexUpdate {
+            added: HashMap::new(),
+            deleted,
+        })
+    }
+
+    pub fn len_in_subtree(&self, relative_subdir: &Path) -> usize {
+        ResourceIndexView::len_in_subtree(self, relative_subdir)
+    }
+
+    pub fn resources_in_subtree(
+        &self,
+        relative_subdir: &Path,
+    ) -> Vec<EntryRef<'_, Id>> {
+        ResourceIndexView::resources_in_subtree(self, relative_subdir)
+    }
+
+    pub(crate) fn relative_path(
+        &self,
+        path: &CanonicalPath,
+    ) -> Result<PathBuf> {
+        pathdiff::diff_paths(path.to_str().unwrap(), self.root.clone())
+            .ok_or(ArklibError::Path("Couldn't calculate path diff".into()))
+    }
+
+    fn insert_entry(&mut self, path: CanonicalPathBuf, entry: IndexEntry<Id>) {
+        log::trace!("[add] {} by path {}", entry.id, path.display());
         let id = entry.clone().id;
 
-        if let std::collections::hash_map::Entry::Vacant(e) =
-            self.id2path.entry(id.clone())
-        {
-            e.insert(path.clone());
-        } else if let Some(nonempty) = self.collisions.get_mut(&id) {
-            *nonempty += 1;
-        } else {
-            self.collisions.insert(id, 2);
+        match Arc::make_mut(&mut self.id2path).entry(id.clone()) {
+            hash_map::Entry::Vacant(e) => {
+                e.insert(path.clone());
+            }
+            hash_map::Entry::Occupied(e) => {
+                *Arc::make_mut(&mut self.collisions)
+                    .entry(id.clone())
+                    .or_insert(1) += 1;
+
+                let paths = Arc::make_mut(&mut self.collided_paths)
+                    .entry(id)
+                    .or_default();
+                paths.insert(e.get().clone());
+                paths.insert(path.clone());
+            }
         }
 
-        self.path2id.insert(path, entry);
+        Arc::make_mut(&mut self.path2id).insert(path, entry);
+    }
+
+    // returns ids which have no paths left
+    fn remove_paths(&mut self, paths: Vec<CanonicalPathBuf>) -> HashSet<Id> {
+        let mut deleted: HashSet<Id> = HashSet::new();
+
+        for path in paths {
+            if let Some(entry) = Arc::make_mut(&mut self.path2id)
+                .remove(path.as_canonical_path())
+            {
+                let k = Arc::make_mut(&mut self.collisions)
+                    .remove(&entry.id)
+                    .unwrap_or(1);
+                if k > 1 {
+                    // an id left with a single path is no longer collided,
+                    // and `id2path` must point to one of the remaining paths
+                    let collided_paths =
+                        Arc::make_mut(&mut self.collided_paths);
+                    let remaining = if k > 2 {
+                        Arc::make_mut(&mut self.collisions)
+                            .insert(entry.id.clone(), k - 1);
+                        collided_paths
+                            .get_mut(&entry.id)
+                            .and_then(|paths| {
+                                paths.remove(&path);
+                                paths.iter().next().cloned()
+                            })
+                    } else {
+                        collided_paths
+                            .remove(&entry.id)
+                            .and_then(|paths| {
+                                paths.into_iter().find(|other| other != &path)
+                            })
+                    };
+                    if let Some(remaining) = remaining {
+                        let id2path = Arc::make_mut(&mut self.id2path);
+                        if id2path.get(&entry.id) == Some(&path) {
+                            id2path.insert(entry.id, remaining);
+                        }
+                    }
+                } else {
+                    log::trace!(
+                        "[delete] {} by path {}",
+                        entry.id,
+                        path.display()
+                    );
+                    Arc::make_mut(&mut self.id2path).remove(&entry.id);
+                    deleted.insert(entry.id);
+                }
+            } else {
+                log::warn!("Path {} was not known", path.display());
+            }
+        }
+
+        deleted
     }
 
     fn forget_path(
@@ -524,9 +1358,11 @@ impl<Id: ResourceId> ResourceIndex<Id> {
         path: &CanonicalPath,
         old_id: Id,
     ) -> Result<IndexUpdate<Id>> {
-        self.path2id.remove(path);
+        Arc::make_mut(&mut self.path2id).remove(path);
 
-        if let Some(collisions) = self.collisions.get_mut(&old_id) {
+        if let Some(collisions) =
+            Arc::make_mut(&mut self.collisions).get_mut(&old_id)
+        {
             debug_assert!(
                 *collisions > 1,
                 "Any collision must involve at least 2 resources"
@@ -534,25 +1370,24 @@ impl<Id: ResourceId> ResourceIndex<Id> {
             *collisions -= 1;
 
             if *collisions == 1 {
-                self.collisions.remove(&old_id);
+                Arc::make_mut(&mut self.collisions).remove(&old_id);
             }
 
-            // minor performance issue:
-            // we must find path of one of the collided
-            // resources and use it as new value
+            // one of the remaining paths of the collided
+            // resources is used as the new value
+            let collided_paths = Arc::make_mut(&mut self.collided_paths);
             let maybe_collided_path =
-                self.path2id.iter().find_map(|(path, entry)| {
-                    if entry.id == old_id {
-                        Some(path)
-                    } else {
-                        None
-                    }
+                collided_paths.get_mut(&old_id).and_then(|paths| {
+                    paths.remove(path);
+                    paths.iter().next().cloned()
                 });
+            if !self.collisions.contains_key(&old_id) {
+                collided_paths.remove(&old_id);
+            }
 
             if let Some(collided_path) = maybe_collided_path {
-                let old_path = self
-                    .id2path
-                    .insert(old_id.clone(), collided_path.clone());
+                let old_path = Arc::make_mut(&mut self.id2path)
+                    .insert(old_id.clone(), collided_path);
 
                 debug_assert_eq!(
                     old_path.unwrap().as_canonical_path(),
@@ -565,7 +1400,7 @@ impl<Id: ResourceId> ResourceIndex<Id> {
                 ));
             }
         } else {
-            self.id2path.remove(&old_id.clone());
+            Arc::make_mut(&mut self.id2path).remove(&old_id.clone());
         }
 
         let mut deleted = HashSet::new();
@@ -578,23 +1413,198 @@ impl<Id: ResourceId> ResourceIndex<Id> {
     }
 }
 
-fn discover_paths<P: AsRef<Path>>(
+impl<'a, Id: ResourceId> IntoIterator for &'a ResourceIndex<Id> {
+    type Item = (&'a CanonicalPathBuf, &'a IndexEntry<Id>);
+    type IntoIter = hash_map::Iter<'a, CanonicalPathBuf, IndexEntry<Id>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The root of the collected index is the deepest directory containing
+/// every resource, use [`ResourceIndex::from_resources`] to choose it
+impl<Id: ResourceId> FromIterator<(CanonicalPathBuf, IndexEntry<Id>)>
+    for ResourceIndex<Id>
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (CanonicalPathBuf, IndexEntry<Id>)>,
+    {
+        let entries: Vec<_> = iter.into_iter().collect();
+        let root_path =
+            common_ancestor(entries.iter().map(|(path, _)| path.as_path()));
+        Self::from_resources(root_path, entries)
+    }
+}
+
+fn common_ancestor<'a>(paths: impl Iterator<Item = &'a Path>) -> PathBuf {
+    let mut ancestor: Option<PathBuf> = None;
+    for path in paths {
+        let parent = path.parent().unwrap_or(path);
+        ancestor = Some(match ancestor {
+            None => parent.to_path_buf(),
+            Some(ancestor) => ancestor
+                .components()
+                .zip(parent.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    ancestor.unwrap_or_default()
+}
+
+// the lock is released when the returned file is dropped
+fn lock_index_file(index_path: &Path) -> Result<File> {
+    let lock_path = index_path.with_extension(INDEX_LOCK_EXTENSION);
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+
+    lock.lock_exclusive()?;
+    Ok(lock)
+}
+
+// readers never create the lock file, so that loading doesn't write
+// anything and works on read-only media; without a lock file no writer
+// has stored the index yet, and storing replaces it atomically anyway
+fn lock_index_file_shared(index_path: &Path) -> Result<Option<File>> {
+    let lock_path = index_path.with_extension(INDEX_LOCK_EXTENSION);
+    let lock = match File::open(&lock_path) {
+        Ok(lock) => lock,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            log::debug!(
+                "Loading the index without a lock, {} doesn't exist",
+                lock_path.display()
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    lock.lock_shared()?;
+    Ok(Some(lock))
+}
+
+fn check_algorithm<Id: ResourceId>(algorithm: &str) -> Result<()> {
+    let expected = algorithm_name::<Id>();
+    if algorithm != expected {
+        return Err(ArklibError::AlgorithmMismatch(
+            expected,
+            algorithm.to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+// lowercased name of the `ResourceId` algorithm, e.g. "crc32" or "blake3",
+// only the last segment is kept for types relying on the default name
+pub(crate) fn algorithm_name<Id: ResourceId>() -> String {
+    Id::name()
+        .rsplit("::")
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+// indexed paths are canonical, so the root must be canonical too
+// for relative paths to be computed correctly
+fn canonicalize_root(root_path: PathBuf) -> PathBuf {
+    fs::canonicalize(&root_path).unwrap_or(root_path)
+}
+
+// canonicalizes paths relative to `root_path`, the ones which don't
+// exist are either skipped or reported as `ResourceNotFound`
+fn resolve_entries<Id: ResourceId, I>(
+    root_path: &Path,
+    entries: I,
+    skip_missing: bool,
+) -> Result<Vec<(CanonicalPathBuf, IndexEntry<Id>)>>
+where
+    I: IntoIterator<Item = (PathBuf, IndexEntry<Id>)>,
+{
+    let mut resolved = Vec::new();
+    for (path, entry) in entries {
+        match CanonicalPathBuf::canonicalize(root_path.join(&path)) {
+            Ok(path) => resolved.push((path, entry)),
+            Err(msg) if skip_missing => {
+                log::warn!(
+                    "Skipping {}, couldn't canonicalize it:\n{}",
+                    path.display(),
+                    msg
+                );
+            }
+            Err(_) => return Err(ArklibError::ResourceNotFound(path)),
+        }
+    }
+    Ok(resolved)
+}
+
+// the walk is lazy, so files can be scanned as they are discovered
+// instead of buffering every path under the root first
+fn discover_paths<P, F>(
     root_path: P,
-) -> HashMap<CanonicalPathBuf, DirEntry> {
+    selector: F,
+) -> impl Iterator<Item = (CanonicalPathBuf, DirEntry)>
+where
+    P: AsRef<Path>,
+    F: Fn(&DirEntry) -> bool,
+{
     log::debug!(
         "Discovering all files under path {}",
         root_path.as_ref().display()
     );
 
+    // on Windows the canonical root has the `\\?\` prefix, so the walk
+    // isn't limited to `MAX_PATH` (260 characters) in deep trees
+    let root_path = fs::canonicalize(root_path.as_ref())
+        .unwrap_or_else(|_| root_path.as_ref().to_path_buf());
+
+    // The `.ark` folder holds the index and other metadata, so it is never
+    // indexed. It is hidden by default, but the `ARK_FOLDER` environment
+    // variable can give it any name, so it's excluded explicitly as well.
+    let ark_folder = ark_folder_path(&root_path);
+
+    // bind mounts can make a directory appear inside itself,
+    // so we remember every directory we have entered
+    let mut visited_dirs = HashSet::new();
+    // `.arkignore` files are loaded as their directories are entered
+    let mut arkignore = ArkIgnore::default();
+    // a link and its target have the same canonical path,
+    // so only the first one found is yielded
+    let mut discovered = HashSet::new();
+
     WalkDir::new(root_path)
         .into_iter()
-        .filter_entry(|entry| !is_hidden(entry))
-        .filter_map(|result| match result {
+        .filter_entry(move |entry| {
+            if !selector(entry)
+                || entry.path() == ark_folder
+                || is_visited_dir(entry, &mut visited_dirs)
+            {
+                return false;
+            }
+
+            let is_dir = entry.file_type().is_dir();
+            if arkignore.is_ignored(entry.path(), is_dir) {
+                log::debug!("Ignoring {}", entry.path().display());
+                return false;
+            }
+            if is_dir {
+                arkignore.enter_dir(entry.path());
+            }
+            true
+        })
+        .filter_map(move |result| match result {
             Ok(entry) => {
                 let path = entry.path();
                 if !entry.file_type().is_dir() {
                     match CanonicalPathBuf::canonicalize(path) {
-                        Ok(canonical_path) => Some((canonical_path, entry)),
+                        Ok(canonical_path) => discovered
+                            .insert(canonical_path.clone())
+                            .then_some((canonical_path, entry)),
                         Err(msg) => {
                             log::warn!(
                                 "Couldn't canonicalize {}:\n{}",
@@ -613,7 +1623,35 @@ fn discover_paths<P: AsRef<Path>>(
                 None
             }
         })
-        .collect()
+}
+
+// most other filesystems can't hold names this long, so the file
+// couldn't be copied there; it's indexed anyway, since it exists here
+fn warn_long_components(path: &Path) {
+    let too_long = path
+        .components()
+        .any(|component| component.as_os_str().len() > MAX_PATH_COMPONENT_LEN);
+    if too_long {
+        log::warn!(
+            "{} has a component longer than {} bytes, \
+            it can't be copied to most other filesystems",
+            path.display(),
+            MAX_PATH_COMPONENT_LEN
+        );
+    }
+}
+
+// the entry of a single path given by a custom discovery,
+// the same as the walk would yield for it
+fn discovered_entry(path: &Path) -> Result<(CanonicalPathBuf, DirEntry)> {
+    let path_buf = CanonicalPathBuf::canonicalize(path)?;
+    let entry = WalkDir::new(&path_buf)
+        .max_depth(0)
+        .into_iter()
+        .next()
+        .ok_or_else(|| ArklibError::Path("Path wasn't found".into()))?
+        .map_err(std::io::Error::from)?;
+    Ok((path_buf, entry))
 }
 
 fn scan_entry<Id>(
@@ -641,32 +1679,164 @@ where
     Ok(IndexEntry { modified, id })
 }
 
+// files left out by `scan_entries`
+#[derive(Default, Debug)]
+struct Skipped {
+    // files which exist but couldn't be read
+    unreadable: Vec<CanonicalPathBuf>,
+    // total size of the files above `max_file_size_bytes`
+    excluded_bytes: u64,
+}
+
+impl Skipped {
+    fn merge(mut self, other: Skipped) -> Self {
+        self.unreadable.extend(other.unreadable);
+        self.excluded_bytes += other.excluded_bytes;
+        self
+    }
+
+    fn report(&self) {
+        if self.excluded_bytes > 0 {
+            log::warn!(
+                "Skipped {} bytes in files above the size limit",
+                self.excluded_bytes
+            );
+        }
+    }
+}
+
+// outcome of scanning a single file
+enum Scanned<Id: ResourceId> {
+    Indexed(IndexEntry<Id>),
+    // not a regular file, or an empty one
+    Ignored,
+    // larger than `max_file_size_bytes`, holds the size of the file
+    Excluded(u64),
+}
+
+// at most `limit` entries are scanned, the rest of the files are skipped;
+// unreadable files are skipped too, unless `strict` is set.
+// Files are hashed in parallel, a chunk at a time, so that discovery
+// doesn't have to finish first and no file is hashed past the limit
 fn scan_entries<Id>(
-    entries: HashMap<CanonicalPathBuf, DirEntry>,
-) -> HashMap<CanonicalPathBuf, IndexEntry<Id>>
+    entries: impl IntoIterator<Item = (CanonicalPathBuf, DirEntry)>,
+    config: &IndexConfig,
+    limit: Option<usize>,
+    strict: bool,
+) -> Result<(HashMap<CanonicalPathBuf, IndexEntry<Id>>, Skipped)>
 where
-    Id: ResourceId,
+    Id: ResourceId + Send,
 {
-    entries
-        .into_iter()
-        .filter_map(|(path_buf, entry)| {
-            let metadata = entry.metadata().ok()?;
+    let limit = limit.unwrap_or(usize::MAX);
+    let mut scanned = HashMap::new();
+    let mut skipped = Skipped::default();
+    let mut entries = entries.into_iter().peekable();
+
+    while entries.peek().is_some() {
+        if scanned.len() >= limit {
+            log::warn!(
+                "Reached the limit of {} entries, skipping the remaining files",
+                limit
+            );
+            break;
+        }
+
+        // every file of the chunk can be indexed without exceeding the limit
+        let chunk_size = SCAN_CHUNK_SIZE.min(limit - scanned.len());
+        let chunk: Vec<_> = entries.by_ref().take(chunk_size).collect();
+        let results: Vec<_> = chunk
+            .into_par_iter()
+            .map(|(path_buf, entry)| {
+                let result = scan_dir_entry(&path_buf, &entry, config);
+                (path_buf, result)
+            })
+            .collect();
 
-            let path = path_buf.as_canonical_path();
-            let result = scan_entry(path, metadata);
+        for (path_buf, result) in results {
             match result {
+                Err(msg) if strict => return Err(msg),
                 Err(msg) => {
-                    log::error!(
-                        "Couldn't retrieve metadata for {}:\n{}",
-                        path.display(),
+                    log::warn!(
+                        "Skipping {}, couldn't read it:\n{}",
+                        path_buf.display(),
                         msg
                     );
-                    None
+                    skipped.unreadable.push(path_buf);
                 }
-                Ok(entry) => Some((path_buf, entry)),
+                Ok(Scanned::Indexed(entry)) => {
+                    scanned.insert(path_buf, entry);
+                }
+                Ok(Scanned::Excluded(size)) => skipped.excluded_bytes += size,
+                Ok(Scanned::Ignored) => {}
             }
-        })
-        .collect()
+        }
+    }
+    Ok((scanned, skipped))
+}
+
+fn scan_dir_entry<Id>(
+    path_buf: &CanonicalPathBuf,
+    entry: &DirEntry,
+    config: &IndexConfig,
+) -> Result<Scanned<Id>>
+where
+    Id: ResourceId,
+{
+    let metadata = entry.metadata().map_err(std::io::Error::from)?;
+    scan_file(path_buf, metadata, config)
+}
+
+fn scan_file<Id>(
+    path_buf: &CanonicalPathBuf,
+    metadata: Metadata,
+    config: &IndexConfig,
+) -> Result<Scanned<Id>>
+where
+    Id: ResourceId,
+{
+    // sockets, FIFOs and devices can't be hashed, opening a FIFO would
+    // even block forever; links are not followed by the walk,
+    // so the metadata of their target is read separately
+    let metadata = if metadata.file_type().is_symlink() {
+        fs::metadata(path_buf)?
+    } else {
+        metadata
+    };
+    let file_type = metadata.file_type();
+    if file_type.is_dir() {
+        // the walk doesn't enter linked directories either
+        log::debug!("Skipping {}: links to a directory", path_buf.display());
+        return Ok(Scanned::Ignored);
+    }
+    if !file_type.is_file() {
+        log::warn!("Skipping {}: not a regular file", path_buf.display());
+        return Ok(Scanned::Ignored);
+    }
+
+    if metadata.len() == 0 {
+        log::debug!("Skipping empty file {}", path_buf.display());
+        return Ok(Scanned::Ignored);
+    }
+
+    if let Some(max_size) = config.max_file_size_bytes {
+        if metadata.len() > max_size {
+            log::warn!(
+                "Skipping {}: {} bytes exceed the limit of {} bytes",
+                path_buf.display(),
+                metadata.len(),
+                max_size
+            );
+            return Ok(Scanned::Excluded(metadata.len()));
+        }
+    }
+
+    scan_entry(path_buf.as_canonical_path(), metadata).map(Scanned::Indexed)
+}
+
+/// The default selector of [`ResourceIndex::build`],
+/// hidden files and directories are not indexed.
+pub fn should_index(entry: &DirEntry) -> bool {
+    !is_hidden(entry)
 }
 
 fn is_hidden(entry: &DirEntry) -> bool {
@@ -677,22 +1847,63 @@ fn is_hidden(entry: &DirEntry) -> bool {
         .unwrap_or(false)
 }
 
+// records the directory by its device and inode,
+// returns `true` if it has been seen already
+#[cfg(target_family = "unix")]
+fn is_visited_dir(entry: &DirEntry, visited: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    if !entry.file_type().is_dir() {
+        return false;
+    }
+    match entry.metadata() {
+        Ok(metadata) => {
+            let first_visit = visited.insert((metadata.dev(), metadata.ino()));
+            if !first_visit {
+                log::warn!(
+                    "Skipping {}: the directory has been visited already",
+                    entry.path().display()
+                );
+            }
+            !first_visit
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn is_visited_dir(
+    _entry: &DirEntry,
+    _visited: &mut HashSet<(u64, u64)>,
+) -> bool {
+    false
+}
+
 #[cfg(test)]
-mod tests {
-    use crate::index::{discover_paths, IndexEntry};
+pub(crate) mod tests {
+    use crate::index::{
+        discover_paths, is_visited_dir, lock_index_file_shared, scan_entries,
+        should_index, IndexConfig, IndexEntry, IndexUpdate,
+        INDEX_LOCK_EXTENSION, INDEX_TMP_EXTENSION,
+    };
     use crate::ResourceIndex;
     use canonical_path::CanonicalPathBuf;
-    use dev_hash::Crc32;
+    use data_error::ArklibError;
+    use dev_hash::{Blake3, Crc32};
+    use fs2::FileExt;
     use fs_atomic_versions::initialize;
+    use fs_storage::{ark_folder_path, INDEX_PATH};
+    use std::collections::{HashMap, HashSet};
     use std::fs::File;
     #[cfg(target_family = "unix")]
     use std::fs::Permissions;
     #[cfg(target_family = "unix")]
     use std::os::unix::fs::PermissionsExt;
 
-    use std::path::PathBuf;
-    use std::time::SystemTime;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
     use uuid::Uuid;
+    use walkdir::WalkDir;
 
     const FILE_SIZE_1: u64 = 10;
     const FILE_SIZE_2: u64 = 11;
@@ -701,11 +1912,16 @@ mod tests {
     const FILE_NAME_2: &str = "test2.txt";
     const FILE_NAME_3: &str = "test3.txt";
 
-    const CRC32_1: Crc32 = Crc32(3817498742);
-    const CRC32_2: Crc32 = Crc32(1804055020);
+    // ids of files with `FILE_SIZE_1` and `FILE_SIZE_2` zero bytes
+    pub(crate) const CRC32_1: Crc32 = Crc32(3817498742);
+    pub(crate) const CRC32_2: Crc32 = Crc32(1804055020);
 
-    fn get_temp_dir() -> PathBuf {
+    // canonical like the root of an index, e.g. the temp dir
+    // is under a symlink on macOS and gets a `\\?\` prefix on Windows
+    pub(crate) fn get_temp_dir() -> PathBuf {
         create_dir_at(std::env::temp_dir())
+            .canonicalize()
+            .expect("Could not canonicalize temp dir")
     }
 
     fn create_dir_at(path: PathBuf) -> PathBuf {
@@ -715,7 +1931,7 @@ mod tests {
         dir_path
     }
 
-    fn create_file_at(
+    pub(crate) fn create_file_at(
         path: PathBuf,
         size: Option<u64>,
         name: Option<&str>,
@@ -733,7 +1949,21 @@ mod tests {
         (file, file_path)
     }
 
-    fn run_test_and_clean_up(
+    // `parent/name` with the given files of zero bytes
+    pub(crate) fn create_dir(
+        parent: &Path,
+        name: &str,
+        files: &[(&str, u64)],
+    ) -> PathBuf {
+        let dir = parent.join(name);
+        std::fs::create_dir(&dir).expect("Could not create temp dir");
+        for (name, size) in files {
+            create_file_at(dir.clone(), Some(*size), Some(name));
+        }
+        dir
+    }
+
+    pub(crate) fn run_test_and_clean_up(
         test: impl FnOnce(PathBuf) + std::panic::UnwindSafe,
     ) {
         initialize();
@@ -767,6 +1997,43 @@ mod tests {
         })
     }
 
+    #[test]
+    fn build_from_iter_should_match_build() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_2));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_3));
+
+            let expected: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let entries = expected
+                .iter()
+                .map(|(path, entry)| {
+                    let name = path.file_name().unwrap();
+                    (PathBuf::from(name), entry.clone())
+                })
+                .collect::<Vec<_>>();
+            let actual = ResourceIndex::build_from_iter(path.clone(), entries)
+                .expect("Should build index from entries");
+
+            assert_eq!(actual.size(), expected.size());
+            assert_eq!(actual.path2id, expected.path2id);
+            assert_eq!(actual.collisions, expected.collisions);
+
+            let missing = vec![(
+                PathBuf::from("missing.txt"),
+                IndexEntry {
+                    modified: SystemTime::UNIX_EPOCH,
+                    id: CRC32_1,
+                },
+            )];
+            let result: Result<ResourceIndex<Crc32>, _> =
+                ResourceIndex::build_from_iter(path.clone(), missing);
+            assert!(matches!(result, Err(ArklibError::ResourceNotFound(_))));
+        })
+    }
+
     #[test]
     fn index_build_should_process_colliding_files_correctly() {
         run_test_and_clean_up(|path| {
@@ -780,8 +2047,43 @@ mod tests {
             assert_eq!(actual.path2id.len(), 2);
             assert_eq!(actual.id2path.len(), 1);
             assert!(actual.id2path.contains_key(&CRC32_1));
-            assert_eq!(actual.collisions.len(), 1);
+            let groups: Vec<_> = actual.collision_groups().collect();
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].0, &CRC32_1);
+            assert_eq!(groups[0].1.len(), 2);
             assert_eq!(actual.size(), 2);
+            assert_eq!(actual.len_unique(), 1);
+        })
+    }
+
+    #[test]
+    fn update_all_should_keep_collision_groups_up_to_date() {
+        run_test_and_clean_up(|path| {
+            let (_, first) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let (_, second) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let (_, third) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(actual.collision_groups().next().unwrap().1.len(), 3);
+
+            std::fs::remove_file(&first).expect("Should remove file");
+            actual.update_all().expect("Should update index");
+            let groups: Vec<_> = actual.collision_groups().collect();
+            assert_eq!(groups.len(), 1);
+            let third = CanonicalPathBuf::canonicalize(&third).unwrap();
+            assert!(groups[0].1.contains(&third));
+            assert_eq!(groups[0].1.len(), 2);
+            assert!(actual.id2path.values().all(|path| path.exists()));
+
+            std::fs::remove_file(&second).expect("Should remove file");
+            actual.update_all().expect("Should update index");
+            assert_eq!(actual.collision_groups().count(), 0);
+            assert_eq!(actual.collisions.len(), 0);
+            assert_eq!(actual.id2path.get(&CRC32_1), Some(&third));
         })
     }
 
@@ -895,50 +2197,122 @@ mod tests {
     }
 
     #[test]
-    fn update_one_should_error_on_new_file() {
+    fn index_new_should_record_collision_with_indexed_file() {
         run_test_and_clean_up(|path| {
-            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
-            let mut index = ResourceIndex::build(path.clone());
-
-            let (_, new_path) =
-                create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+            let (_, first) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let mut index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
 
-            let update = index.update_one(&new_path, CRC32_2);
+            let (_, second) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            index
+                .index_new(&second)
+                .expect("Should update index correctly");
 
-            assert!(update.is_err())
+            let groups: Vec<_> = index.collision_groups().collect();
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].0, &CRC32_1);
+            assert_eq!(groups[0].1.len(), 2);
+            for file in [first, second] {
+                let file = CanonicalPathBuf::canonicalize(&file).unwrap();
+                assert!(groups[0].1.contains(&file));
+            }
+            assert_eq!(index.collisions.get(&CRC32_1), Some(&2));
+            assert_eq!(index.len_unique(), 1);
         })
     }
 
     #[test]
-    fn update_one_should_index_delete_file_successfully() {
+    fn get_resource_by_path_should_accept_relative_and_absolute_paths() {
         run_test_and_clean_up(|path| {
-            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let (_, file_path) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
 
-            let mut actual = ResourceIndex::build(path.clone());
+            let relative = index
+                .get_resource_by_path(FILE_NAME_1)
+                .expect("Should find the resource by relative path");
+            let absolute = index
+                .get_resource_by_path(&file_path)
+                .expect("Should find the resource by absolute path");
 
-            let mut file_path = path.clone();
-            file_path.push(FILE_NAME_1);
-            std::fs::remove_file(file_path.clone())
-                .expect("Should remove file successfully");
+            assert_eq!(relative.id, CRC32_1);
+            assert_eq!(relative, absolute);
+            assert!(index.get_resource_by_path(FILE_NAME_2).is_none());
+            assert_eq!(
+                index
+                    .get_resource_by_path_or_error(FILE_NAME_1)
+                    .unwrap(),
+                relative
+            );
+            assert!(matches!(
+                index.get_resource_by_path_or_error(FILE_NAME_2),
+                Err(ArklibError::ResourceNotFound(path))
+                    if path == Path::new(FILE_NAME_2)
+            ));
+            assert!(index.contains_path(Path::new(FILE_NAME_1)));
+            assert!(index.contains_path(&file_path));
+            assert!(!index.contains_path(Path::new(FILE_NAME_2)));
+        })
+    }
 
-            let update = actual
-                .update_one(&file_path.clone(), CRC32_1)
-                .expect("Should update index successfully");
+    #[test]
+    fn index_update_merge_should_accumulate_net_changes() {
+        // any existing path will do, nothing is read from it
+        let path = CanonicalPathBuf::canonicalize(env!("CARGO_MANIFEST_DIR"))
+            .expect("CanonicalPathBuf should be fine");
+
+        let mut acc: IndexUpdate<Crc32> = IndexUpdate::default();
+        assert!(acc.is_empty());
+        acc.extend(IndexUpdate {
+            deleted: HashSet::from([CRC32_1]),
+            added: HashMap::new(),
+        });
+        acc.extend(IndexUpdate {
+            deleted: HashSet::new(),
+            added: HashMap::from([(path.clone(), CRC32_2)]),
+        });
+        assert_eq!(acc.deleted, HashSet::from([CRC32_1]));
+        assert_eq!(acc.added, HashMap::from([(path, CRC32_2)]));
+        assert_eq!(acc.len(), 2);
+
+        let acc = acc.merge(IndexUpdate {
+            deleted: HashSet::from([CRC32_2]),
+            added: HashMap::new(),
+        });
+        assert!(acc.added.is_empty());
+        assert_eq!(acc.deleted, HashSet::from([CRC32_1]));
+    }
 
-            assert_eq!(actual.root, path.clone());
-            assert_eq!(actual.path2id.len(), 0);
-            assert_eq!(actual.id2path.len(), 0);
-            assert_eq!(actual.collisions.len(), 0);
-            assert_eq!(actual.size(), 0);
-            assert_eq!(update.deleted.len(), 1);
-            assert_eq!(update.added.len(), 0);
+    #[test]
+    fn build_strict_should_match_build_on_readable_files() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(0), Some(FILE_NAME_2));
 
-            assert!(update.deleted.contains(&CRC32_1))
+            let strict: ResourceIndex<Crc32> = ResourceIndex::build_strict(
+                path.clone(),
+                IndexConfig::default(),
+            )
+            .expect("Should build index correctly");
+            let lenient: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            assert_eq!(strict.size(), 1);
+            assert_eq!(strict.path2id, lenient.path2id);
         })
     }
 
+    #[cfg(target_family = "unix")]
     #[test]
-    fn update_all_should_error_on_files_without_permissions() {
+    #[ignore = "file permissions are not enforced for root, \
+        run with `--ignored` as an unprivileged user"]
+    fn build_strict_should_fail_on_unreadable_files() {
         run_test_and_clean_up(|path| {
             create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
             let (file, _) = create_file_at(
@@ -946,29 +2320,823 @@ mod tests {
                 Some(FILE_SIZE_2),
                 Some(FILE_NAME_2),
             );
+            file.set_permissions(Permissions::from_mode(0o000))
+                .expect("Should set permissions");
 
-            let mut actual: ResourceIndex<Crc32> =
-                ResourceIndex::build(path.clone());
+            let strict: data_error::Result<ResourceIndex<Crc32>> =
+                ResourceIndex::build_strict(
+                    path.clone(),
+                    IndexConfig::default(),
+                );
+            assert!(strict.is_err());
 
-            assert_eq!(actual.collisions.len(), 0);
-            assert_eq!(actual.size(), 2);
-            #[cfg(target_family = "unix")]
-            file.set_permissions(Permissions::from_mode(0o222))
-                .expect("Should be fine");
+            let lenient: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(lenient.size(), 1);
+        })
+    }
 
-            let update = actual
-                .update_all()
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn build_should_ignore_symlinks_to_directories() {
+        run_test_and_clean_up(|path| {
+            let nested = path.join("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            // a link to a directory is discovered as a file
+            std::os::unix::fs::symlink(&nested, path.join("link"))
+                .expect("Should create symlink");
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build_strict(
+                path.clone(),
+                IndexConfig::default(),
+            )
+            .expect("Should build index correctly");
+            assert_eq!(index.size(), 1);
+            assert!(index
+                .path2id
+                .keys()
+                .all(|indexed| !indexed.ends_with("link")));
+        })
+    }
+
+    #[test]
+    fn load_should_fail_on_algorithm_mismatch() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            index.store().expect("Should store index");
+
+            let loaded: ResourceIndex<Crc32> =
+                ResourceIndex::load(path.clone()).expect("Should load index");
+            assert_eq!(loaded.id2path, index.id2path);
+
+            let result: Result<ResourceIndex<Blake3>, _> =
+                ResourceIndex::load(path.clone());
+            assert!(matches!(
+                result,
+                Err(ArklibError::AlgorithmMismatch(expected, found))
+                    if expected == "blake3" && found == "crc32"
+            ));
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn symlinks_to_files_outside_root_should_be_canonicalized() {
+        run_test_and_clean_up(|path| {
+            let root = path.join("root");
+            std::fs::create_dir(&root).expect("Should create directory");
+            let (_, target) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            std::os::unix::fs::symlink(&target, root.join(FILE_NAME_2))
+                .expect("Should create symlink");
+
+            // the root is given with a `..` component
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(root.join("..").join("root"));
+            let target = CanonicalPathBuf::canonicalize(&target).unwrap();
+            assert_eq!(index.id2path[&CRC32_1], target);
+
+            index.store().expect("Should store index");
+            let loaded: ResourceIndex<Crc32> =
+                ResourceIndex::load(root.clone()).expect("Should load index");
+            assert_eq!(loaded.id2path[&CRC32_1], target);
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn symlinks_should_be_checked_by_the_size_of_their_target() {
+        run_test_and_clean_up(|path| {
+            let root = path.join("root");
+            std::fs::create_dir(&root).expect("Should create directory");
+            let (_, large) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_1),
+            );
+            let (_, empty) =
+                create_file_at(path.clone(), Some(0), Some(FILE_NAME_2));
+            std::os::unix::fs::symlink(&large, root.join("large"))
+                .expect("Should create symlink");
+            std::os::unix::fs::symlink(&empty, root.join("empty"))
+                .expect("Should create symlink");
+
+            let config = IndexConfig {
+                max_file_size_bytes: Some(FILE_SIZE_1),
+                ..Default::default()
+            };
+            let entries = discover_paths(&root, should_index);
+            let (entries, skipped) =
+                scan_entries::<Crc32>(entries, &config, None, true)
+                    .expect("Should skip links to large and empty files");
+
+            assert!(entries.is_empty());
+            assert_eq!(skipped.excluded_bytes, FILE_SIZE_2);
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn update_all_should_keep_paths_stable_with_symlinked_root() {
+        run_test_and_clean_up(|path| {
+            let actual_root = path.join("actual_root");
+            std::fs::create_dir(&actual_root).expect("Should create directory");
+            let (_, file) = create_file_at(
+                actual_root.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            let root = path.join("root");
+            std::os::unix::fs::symlink(&actual_root, &root)
+                .expect("Should create symlink");
+
+            let mut index: ResourceIndex<Crc32> =
+                ResourceIndex::build(root.clone());
+            let actual_root = actual_root.canonicalize().unwrap();
+            assert_eq!(index.root, actual_root);
+
+            std::fs::rename(&file, actual_root.join(FILE_NAME_2))
+                .expect("Should move file");
+            let update = index
+                .update_all()
+                .expect("Should update index correctly");
+
+            let moved =
+                CanonicalPathBuf::canonicalize(actual_root.join(FILE_NAME_2))
+                    .unwrap();
+            assert_eq!(update.deleted, HashSet::from([CRC32_1]));
+            assert_eq!(update.added, HashMap::from([(moved.clone(), CRC32_1)]));
+            assert_eq!(index.id2path[&CRC32_1], moved);
+            assert!(index
+                .path2id
+                .keys()
+                .all(|path| path.starts_with(&actual_root)));
+
+            // nothing is reported again when updating through the symlink
+            assert!(index.update_all().unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn build_incremental_should_only_hash_modified_files() {
+        run_test_and_clean_up(|path| {
+            let (file, _) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+
+            let built: ResourceIndex<Crc32> =
+                ResourceIndex::build_incremental(path.clone())
+                    .expect("Should build index without a baseline");
+            assert!(built.id2path.contains_key(&CRC32_1));
+            built.store().expect("Should store index");
+
+            // the content changes, but the timestamp is restored,
+            // so the stale id is a proof that the file wasn't hashed again
+            let modified = file.metadata().unwrap().modified().unwrap();
+            file.set_len(FILE_SIZE_2 + 1)
+                .expect("Should resize file");
+            file.set_modified(modified)
+                .expect("Should set timestamp");
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_2));
+
+            let actual: ResourceIndex<Crc32> =
+                ResourceIndex::build_incremental(path.clone())
+                    .expect("Should build index from the stored one");
+
+            assert_eq!(actual.size(), 2);
+            assert!(actual.id2path.contains_key(&CRC32_1));
+            assert!(actual.id2path.contains_key(&CRC32_2));
+        })
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn store_async_should_write_same_index() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_2));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+
+            index.store().expect("Should store index");
+            let stored = std::fs::read(&index_path).unwrap();
+            std::fs::remove_file(&index_path).unwrap();
+
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(index.store_async())
+                .expect("Should store index asynchronously");
+            assert_eq!(std::fs::read(&index_path).unwrap(), stored);
+        })
+    }
+
+    #[test]
+    fn store_should_be_reproducible() {
+        run_test_and_clean_up(|path| {
+            let modified = SystemTime::now();
+            for _ in 0..10 {
+                let (file, _) =
+                    create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+                file.set_modified(modified)
+                    .expect("Should set timestamp");
+            }
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+
+            // every build iterates its maps in a different order
+            let mut stored = Vec::new();
+            for _ in 0..2 {
+                let index: ResourceIndex<Crc32> =
+                    ResourceIndex::build(path.clone());
+                index.store().expect("Should store index");
+                stored.push(
+                    std::fs::read(&index_path).expect("Should read index"),
+                );
+            }
+
+            assert_eq!(stored[0], stored[1]);
+        })
+    }
+
+    #[test]
+    fn store_should_not_be_affected_by_interrupted_write() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            index.store().expect("Should store index");
+
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+            let stored_size = std::fs::metadata(&index_path)
+                .expect("Index should be stored")
+                .len();
+
+            // a crash in the middle of the next `store` leaves
+            // a partially written temporary file behind
+            let tmp_path = index_path.with_extension(INDEX_TMP_EXTENSION);
+            std::fs::write(&tmp_path, "1700000000000 38")
+                .expect("Should write temporary file");
+
+            assert_eq!(
+                std::fs::metadata(&index_path).unwrap().len(),
+                stored_size
+            );
+            let loaded: ResourceIndex<Crc32> =
+                ResourceIndex::load(path.clone()).expect("Should load index");
+            assert_eq!(loaded.id2path, index.id2path);
+
+            index.store().expect("Should store index");
+            assert!(!tmp_path.exists());
+            assert_eq!(
+                std::fs::metadata(&index_path).unwrap().len(),
+                stored_size
+            );
+        })
+    }
+
+    #[test]
+    fn import_from_index_file_should_support_all_versions() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+            std::fs::create_dir_all(ark_folder_path(&path))
+                .expect("Should create ark folder");
+
+            let v1 = format!("1700000000000 {} {}\n", CRC32_1, FILE_NAME_1);
+            std::fs::write(&index_path, &v1).expect("Should write index");
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::import_from_index_file(&index_path)
+                    .expect("Should import version 1");
+            assert_eq!(index.size(), 1);
+            assert!(index.id2path.contains_key(&CRC32_1));
+
+            let v2 = format!("version 2\nalgorithm crc32\n{}", v1);
+            std::fs::write(&index_path, v2).expect("Should write index");
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::import_from_index_file(&index_path)
+                    .expect("Should import version 2");
+            assert_eq!(index.size(), 1);
+
+            // only version 2 has the algorithm header
+            let with_header = format!("algorithm crc32\n{}", v1);
+            std::fs::write(&index_path, with_header)
+                .expect("Should write index");
+            let result: Result<ResourceIndex<Crc32>, _> =
+                ResourceIndex::import_from_index_file(&index_path);
+            assert!(result.is_err());
+
+            let v3 = format!("version 3\n{}", v1);
+            std::fs::write(&index_path, v3).expect("Should write index");
+            let result: Result<ResourceIndex<Crc32>, _> =
+                ResourceIndex::import_from_index_file(&index_path);
+            assert!(result.is_err());
+        })
+    }
+
+    #[test]
+    fn partition_by_collision_should_separate_unique_files() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_2));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_3));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let (unique, collided) = index.partition_by_collision();
+
+            assert_eq!(unique.len(), 1);
+            assert_eq!(unique[0].1.id, CRC32_2);
+            assert_eq!(collided.len(), 1);
+            assert_eq!(collided[&CRC32_1].len(), 2);
+        })
+    }
+
+    #[test]
+    fn store_should_wait_for_readers_lock() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            index.store().expect("Should store index");
+
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+            let lock = lock_index_file_shared(&index_path)
+                .expect("Should acquire shared lock")
+                .expect("Should open lock file");
+            let lock_path = index_path.with_extension(INDEX_LOCK_EXTENSION);
+            let other = File::open(&lock_path).expect("Should open lock file");
+            assert!(other.try_lock_exclusive().is_err());
+
+            // readers can share the lock
+            ResourceIndex::<Crc32>::load(path.clone())
+                .expect("Should load index");
+            drop(lock);
+
+            assert!(other.try_lock_exclusive().is_ok());
+        })
+    }
+
+    #[test]
+    fn load_should_not_create_lock_file() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            index.store().expect("Should store index");
+
+            let index_path = ark_folder_path(&path).join(INDEX_PATH);
+            let lock_path = index_path.with_extension(INDEX_LOCK_EXTENSION);
+            std::fs::remove_file(&lock_path).expect("Should remove lock file");
+
+            let loaded = ResourceIndex::<Crc32>::load(path.clone())
+                .expect("Should load index without a lock file");
+            assert_eq!(loaded.size(), 1);
+            assert!(!lock_path.exists());
+        })
+    }
+
+    #[test]
+    fn get_resource_by_id_any_should_return_one_of_copies() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_2));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let (path, entry) = index
+                .get_resource_by_id_any(&CRC32_1)
+                .expect("Should find resource");
+            assert_eq!(entry.id, CRC32_1);
+            assert!(path.ends_with(FILE_NAME_1) || path.ends_with(FILE_NAME_2));
+            assert!(index.get_resource_by_id_any(&CRC32_2).is_none());
+            assert!(index.contains_id(&CRC32_1));
+            assert!(!index.contains_id(&CRC32_2));
+        })
+    }
+
+    #[test]
+    fn get_paths_by_id_should_return_every_path_of_id() {
+        run_test_and_clean_up(|path| {
+            let (_, first) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let (_, second) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let (_, unique) =
+                create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let paths: HashSet<_> = index
+                .get_paths_by_id(&CRC32_1)
+                .expect("Should find the collided id")
+                .collect();
+            let first = CanonicalPathBuf::canonicalize(&first).unwrap();
+            let second = CanonicalPathBuf::canonicalize(&second).unwrap();
+            assert_eq!(paths, HashSet::from([&first, &second]));
+
+            let paths: Vec<_> = index
+                .get_paths_by_id(&CRC32_2)
+                .expect("Should find the unique id")
+                .collect();
+            let unique = CanonicalPathBuf::canonicalize(&unique).unwrap();
+            assert_eq!(paths, vec![&unique]);
+
+            assert!(index.get_paths_by_id(&Crc32(0)).is_none());
+        })
+    }
+
+    #[test]
+    fn get_resource_by_content_should_find_all_copies() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_2));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_3));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let copies = index
+                .get_resource_by_content(&[0; FILE_SIZE_1 as usize])
+                .expect("Should hash content")
+                .expect("Content should be indexed");
+            assert_eq!(copies.len(), 2);
+
+            let single = index
+                .get_resource_by_content(&[0; FILE_SIZE_2 as usize])
+                .expect("Should hash content")
+                .expect("Content should be indexed");
+            assert_eq!(single.len(), 1);
+            assert_eq!(single[0].1.id, CRC32_2);
+
+            let missing = index
+                .get_resource_by_content(b"not indexed")
+                .expect("Should hash content");
+            assert!(missing.is_none());
+        })
+    }
+
+    #[test]
+    fn len_in_subtree_should_count_nested_files() {
+        run_test_and_clean_up(|path| {
+            let nested = path.join("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_3),
+            );
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            assert_eq!(index.len_in_subtree(Path::new("nested")), 2);
+            assert_eq!(index.len_in_subtree(Path::new("nest")), 0);
+            assert_eq!(index.len_in_subtree(Path::new("")), 3);
+        })
+    }
+
+    #[test]
+    fn resources_in_subtree_should_return_nested_entries() {
+        run_test_and_clean_up(|path| {
+            let nested = path.join("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let nested = index.resources_in_subtree(Path::new("nested"));
+            assert_eq!(nested.len(), 1);
+            assert_eq!(nested[0].1.id, CRC32_2);
+
+            let all = index.resources_in_subtree(Path::new(""));
+            assert_eq!(all.len(), index.size());
+        })
+    }
+
+    #[test]
+    fn build_with_discovery_should_index_only_discovered_paths() {
+        run_test_and_clean_up(|path| {
+            let (_, file_path) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_2));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_discovery(&path, |root| {
+                    Ok(vec![file_path.clone(), root.join("missing")])
+                })
+                .expect("Should build index correctly");
+
+            assert_eq!(index.size(), 1);
+            assert!(index.id2path.contains_key(&CRC32_1));
+
+            let result: Result<ResourceIndex<Crc32>, _> =
+                ResourceIndex::build_with_discovery(&path, |_| {
+                    Err(ArklibError::Path("discovery failed".into()))
+                });
+            assert!(result.is_err());
+        })
+    }
+
+    #[test]
+    fn to_hashmap_should_map_paths_to_ids() {
+        run_test_and_clean_up(|path| {
+            let (_, file_path) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_2));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let map = index.to_hashmap();
+
+            assert_eq!(map.len(), 2);
+            assert_eq!(map[&file_path.canonicalize().unwrap()], CRC32_1);
+        })
+    }
+
+    #[test]
+    fn update_one_should_error_on_new_file() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            let mut index = ResourceIndex::build(path.clone());
+
+            let (_, new_path) =
+                create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+
+            let update = index.update_one(&new_path, CRC32_2);
+
+            assert!(matches!(update, Err(ArklibError::ResourceNotFound(_))))
+        })
+    }
+
+    #[test]
+    fn update_one_should_index_delete_file_successfully() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let (_, colliding_path) =
+                create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+
+            let mut actual = ResourceIndex::build(path.clone());
+            assert_eq!(actual.collisions.len(), 1);
+            assert_eq!(actual.collision_groups().count(), 1);
+
+            let mut file_path = path.clone();
+            file_path.push(FILE_NAME_1);
+            std::fs::remove_file(file_path.clone())
+                .expect("Should remove file successfully");
+            std::fs::remove_file(colliding_path)
+                .expect("Should remove file successfully");
+
+            let update = actual
+                .update_one(&file_path.clone(), CRC32_1)
+                .expect("Should update index successfully");
+
+            assert_eq!(actual.root, path.clone());
+            assert_eq!(actual.path2id.len(), 0);
+            assert_eq!(actual.id2path.len(), 0);
+            assert_eq!(actual.collisions.len(), 0);
+            assert_eq!(actual.collision_groups().count(), 0);
+            assert_eq!(actual.size(), 0);
+            assert_eq!(update.deleted.len(), 1);
+            assert_eq!(update.added.len(), 0);
+
+            assert!(update.deleted.contains(&CRC32_1))
+        })
+    }
+
+    #[test]
+    fn retain_should_keep_index_consistent() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_2));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_3));
+
+            let mut index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(index.collision_groups().count(), 1);
+
+            index.retain(|path, _| !path.ends_with(FILE_NAME_1));
+
+            assert_eq!(index.size(), 2);
+            assert_eq!(index.id2path.len(), 2);
+            assert_eq!(index.collision_groups().count(), 0);
+            assert!(index.id2path[&CRC32_1].ends_with(FILE_NAME_2));
+
+            index.retain(|_, entry| entry.id != CRC32_2);
+
+            assert_eq!(index.size(), 1);
+            assert_eq!(index.id2path.len(), 1);
+            assert!(!index.id2path.contains_key(&CRC32_2));
+        })
+    }
+
+    #[test]
+    fn update_all_should_error_on_files_without_permissions() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let (file, _) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            assert_eq!(actual.collisions.len(), 0);
+            assert_eq!(actual.size(), 2);
+            #[cfg(target_family = "unix")]
+            file.set_permissions(Permissions::from_mode(0o222))
+                .expect("Should be fine");
+
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+
+            assert_eq!(actual.collisions.len(), 0);
+            assert_eq!(actual.size(), 2);
+            assert_eq!(update.deleted.len(), 0);
+            assert_eq!(update.added.len(), 0);
+        })
+    }
+
+    #[test]
+    fn update_all_should_ignore_timestamps_going_backward() {
+        run_test_and_clean_up(|path| {
+            let (file, _) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let earlier =
+                SystemTime::now() - std::time::Duration::from_secs(3600);
+            file.set_modified(earlier)
+                .expect("Should set timestamp");
+
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+
+            assert!(update.is_empty());
+            assert_eq!(actual.size(), 1);
+        })
+    }
+
+    #[test]
+    fn update_all_should_not_rehash_files_with_unchanged_timestamp() {
+        run_test_and_clean_up(|path| {
+            let (file, _) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let modified = file.metadata().unwrap().modified().unwrap();
+
+            // the content changes, but the timestamp is restored,
+            // so the stale id is a proof that the file wasn't hashed again
+            file.set_len(FILE_SIZE_2)
+                .expect("Should resize file");
+            file.set_modified(modified)
+                .expect("Should set timestamp");
+
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+
+            assert!(update.is_empty());
+            assert!(actual.id2path.contains_key(&CRC32_1));
+        })
+    }
+
+    #[test]
+    fn update_all_should_detect_files_older_than_the_newest_one() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let (file, modified_path) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+            file.set_modified(
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1),
+            )
+            .expect("Should set timestamp");
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            // still older than the first file
+            file.set_len(FILE_SIZE_2 + 1)
+                .expect("Should modify file");
+            file.set_modified(
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60),
+            )
+            .expect("Should set timestamp");
+
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+
+            assert!(update.deleted.contains(&CRC32_2));
+            let modified_path =
+                CanonicalPathBuf::canonicalize(&modified_path).unwrap();
+            assert!(update.added.contains_key(&modified_path));
+            assert!(!actual.contains_id(&CRC32_2));
+        })
+    }
+
+    #[test]
+    fn update_all_should_detect_new_files_with_old_timestamps() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert!(actual.update_all().unwrap().is_empty());
+
+            // copied files can keep a timestamp older than every indexed one
+            let (file, _) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+            file.set_modified(SystemTime::UNIX_EPOCH)
+                .expect("Should set timestamp");
+
+            let update = actual
+                .update_all()
                 .expect("Should update index correctly");
 
-            assert_eq!(actual.collisions.len(), 0);
-            assert_eq!(actual.size(), 2);
-            assert_eq!(update.deleted.len(), 0);
-            assert_eq!(update.added.len(), 0);
+            assert_eq!(update.added.len(), 1);
+            assert!(actual.id2path.contains_key(&CRC32_2));
         })
     }
 
     // error cases
 
+    #[cfg(target_family = "unix")]
+    #[test]
+    #[ignore = "permissions are not enforced for root, \
+        run with `--ignored` as an unprivileged user"]
+    fn store_should_fail_on_read_only_root() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            std::fs::set_permissions(&path, Permissions::from_mode(0o555))
+                .expect("Should be fine");
+            let result = index.store();
+            std::fs::set_permissions(&path, Permissions::from_mode(0o755))
+                .expect("Should be fine");
+
+            assert!(matches!(
+                result,
+                Err(ArklibError::Io(e))
+                    if e.kind() == std::io::ErrorKind::PermissionDenied
+            ));
+        })
+    }
+
     #[test]
     fn update_one_should_not_update_absent_path() {
         run_test_and_clean_up(|path| {
@@ -1017,6 +3185,149 @@ mod tests {
         })
     }
 
+    #[test]
+    fn should_not_index_file_above_size_limit() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+
+            let config = IndexConfig {
+                max_file_size_bytes: Some(FILE_SIZE_1),
+                ..Default::default()
+            };
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(path.clone(), config);
+
+            assert_eq!(actual.path2id.len(), 1);
+            assert!(actual.id2path.contains_key(&CRC32_1));
+
+            create_file_at(path.clone(), Some(FILE_SIZE_2 + 1), None);
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+
+            assert_eq!(update.added.len(), 0);
+            assert_eq!(actual.path2id.len(), 1);
+        })
+    }
+
+    #[test]
+    fn should_report_bytes_above_size_limit() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+            create_file_at(path.clone(), Some(FILE_SIZE_2 + 1), None);
+
+            let config = IndexConfig {
+                max_file_size_bytes: Some(FILE_SIZE_1),
+                ..Default::default()
+            };
+            let entries = discover_paths(&path, should_index);
+            let (entries, skipped) =
+                scan_entries::<Crc32>(entries, &config, None, false)
+                    .expect("Should skip unreadable files");
+
+            assert_eq!(entries.len(), 1);
+            assert_eq!(skipped.excluded_bytes, 2 * FILE_SIZE_2 + 1);
+        })
+    }
+
+    #[test]
+    fn load_should_keep_the_given_config() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+
+            let config = IndexConfig {
+                max_file_size_bytes: Some(FILE_SIZE_1),
+                ..Default::default()
+            };
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(path.clone(), config.clone());
+            index.store().expect("Should store index");
+
+            let mut loaded: ResourceIndex<Crc32> =
+                ResourceIndex::load_with_config(path.clone(), config.clone())
+                    .expect("Should load index");
+            assert_eq!(loaded.config, config);
+
+            create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+            let update = loaded.update_all().expect("Should update index");
+            assert!(update.added.is_empty());
+
+            let provided: ResourceIndex<Crc32> =
+                ResourceIndex::provide_with_config(path.clone(), config)
+                    .expect("Should provide index");
+            assert_eq!(provided.size(), 1);
+        })
+    }
+
+    #[test]
+    fn should_not_index_more_than_max_entries() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(FILE_NAME_2));
+
+            let config = IndexConfig {
+                max_entries: Some(1),
+                ..Default::default()
+            };
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(path.clone(), config);
+            assert_eq!(actual.path2id.len(), 1);
+
+            // the limit is checked against the current size of the index
+            let indexed = actual.path2id.keys().next().unwrap().clone();
+            let other = if indexed.ends_with(FILE_NAME_1) {
+                path.join(FILE_NAME_2)
+            } else {
+                path.join(FILE_NAME_1)
+            };
+            std::fs::remove_file(other).expect("Should remove file");
+            create_file_at(path.clone(), Some(FILE_SIZE_2 + 1), None);
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+            assert_eq!(update.added.len(), 0);
+            assert_eq!(actual.path2id.len(), 1);
+
+            // existing entries are still updated when the index is full
+            std::fs::write(&indexed, "updated content")
+                .expect("Should modify file");
+            let modified = SystemTime::now() + Duration::from_secs(1);
+            File::options()
+                .write(true)
+                .open(&indexed)
+                .and_then(|file| file.set_modified(modified))
+                .expect("Should set timestamp");
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+            assert_eq!(update.added.len(), 1);
+            assert!(update.added.contains_key(&indexed));
+            assert_eq!(actual.path2id.len(), 1);
+        })
+    }
+
+    #[test]
+    fn store_should_not_create_ark_folder_without_persistence() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+
+            let config = IndexConfig {
+                no_persist: true,
+                ..Default::default()
+            };
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(path.clone(), config);
+
+            index
+                .store()
+                .expect("Should skip storing the index");
+            assert_eq!(index.size(), 1);
+            assert!(!ark_folder_path(&path).exists());
+        })
+    }
+
     #[test]
     fn should_not_index_hidden_file() {
         run_test_and_clean_up(|path| {
@@ -1031,6 +3342,33 @@ mod tests {
         })
     }
 
+    #[test]
+    fn should_not_index_files_in_ark_folder() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let ark_folder = ark_folder_path(&path);
+            std::fs::create_dir_all(&ark_folder)
+                .expect("Should create directory");
+            create_file_at(
+                ark_folder.clone(),
+                Some(FILE_SIZE_2),
+                Some("my_file.txt"),
+            );
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(actual.size(), 1);
+            assert!(!actual.id2path.contains_key(&CRC32_2));
+
+            create_file_at(ark_folder, Some(FILE_SIZE_2), Some(FILE_NAME_2));
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+            assert!(update.is_empty());
+            assert_eq!(actual.size(), 1);
+        })
+    }
+
     #[test]
     fn should_not_index_1_empty_directory() {
         run_test_and_clean_up(|path| {
@@ -1051,8 +3389,327 @@ mod tests {
         run_test_and_clean_up(|path| {
             let mut missing_path = path.clone();
             missing_path.push("missing/directory");
-            let actual = discover_paths(missing_path);
-            assert_eq!(actual.len(), 0);
+            let actual = discover_paths(missing_path, should_index);
+            assert_eq!(actual.count(), 0);
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn discover_paths_should_not_follow_cyclic_links() {
+        run_test_and_clean_up(|path| {
+            let mut nested = path.clone();
+            nested.push("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            std::os::unix::fs::symlink(&path, nested.join("loop"))
+                .expect("Should create symlink");
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            assert_eq!(index.size(), 1);
+            assert!(index
+                .get_resource_by_path(nested.join(FILE_NAME_1))
+                .is_some());
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn discover_paths_should_yield_linked_files_once() {
+        run_test_and_clean_up(|path| {
+            let (_, target) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            std::os::unix::fs::symlink(&target, path.join(FILE_NAME_2))
+                .expect("Should create symlink");
+
+            let target = CanonicalPathBuf::canonicalize(&target).unwrap();
+            let paths: Vec<_> = discover_paths(&path, should_index)
+                .map(|(path, _)| path)
+                .collect();
+            assert_eq!(paths, vec![target]);
+        })
+    }
+
+    // the walk in `discover_paths` doesn't follow links, so bind mounts are
+    // the only way to enter a directory twice there; following a link
+    // enters the same device and inode as well
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn is_visited_dir_should_skip_directories_entered_twice() {
+        run_test_and_clean_up(|path| {
+            let nested = path.join("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            std::os::unix::fs::symlink(&nested, path.join("link"))
+                .expect("Should create symlink");
+
+            let mut visited_dirs = HashSet::new();
+            let files = WalkDir::new(&path)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(|entry| !is_visited_dir(entry, &mut visited_dirs))
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .count();
+
+            assert_eq!(files, 1);
+        })
+    }
+
+    #[test]
+    fn build_should_respect_arkignore() {
+        run_test_and_clean_up(|path| {
+            let mut nested = path.clone();
+            nested.push("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            std::fs::write(path.join(".arkignore"), "nested/\n")
+                .expect("Should write .arkignore");
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(
+                nested.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            assert_eq!(index.size(), 1);
+            assert!(index.id2path.contains_key(&CRC32_1));
+        })
+    }
+
+    #[test]
+    fn build_with_selector_should_replace_default_selection() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            create_file_at(path.clone(), Some(FILE_SIZE_2), Some(".hidden"));
+
+            let mut index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_selector(path.clone(), |entry| {
+                    entry.file_type().is_dir()
+                        || entry.file_name() != FILE_NAME_1
+                })
+                .expect("Should build index");
+
+            assert_eq!(index.size(), 1);
+            assert!(index.id2path.contains_key(&CRC32_2));
+
+            // the selector is kept for updates
+            create_file_at(path.clone(), Some(FILE_SIZE_2 + 1), Some(".other"));
+            let update = index.update_all().expect("Should update index");
+            assert_eq!(update.added.len(), 1);
+            assert_eq!(index.size(), 2);
+
+            let missing: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_selector(
+                    path.join("missing"),
+                    should_index,
+                )
+                .expect("Should build empty index");
+            assert_eq!(missing.size(), 0);
+        })
+    }
+
+    #[test]
+    fn build_should_skip_unreadable_files() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let (_, unreadable) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+            let entries: Vec<_> = discover_paths(&path, should_index).collect();
+
+            // removed after discovery, so even root can't read it
+            let canonical =
+                CanonicalPathBuf::canonicalize(&unreadable).unwrap();
+            std::fs::remove_file(&unreadable).expect("Should remove file");
+
+            let (scanned, skipped) = scan_entries::<Crc32>(
+                entries,
+                &IndexConfig::default(),
+                None,
+                false,
+            )
+            .expect("Should skip unreadable files");
+            assert_eq!(scanned.len(), 1);
+            assert_eq!(skipped.unreadable, vec![canonical]);
+        })
+    }
+
+    #[test]
+    fn index_should_be_collected_from_resources() {
+        run_test_and_clean_up(|path| {
+            let nested = path.join("nested");
+            std::fs::create_dir(&nested).expect("Should create directory");
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            create_file_at(nested, Some(FILE_SIZE_2), None);
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let resources = index
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.clone()));
+
+            let collected: ResourceIndex<Crc32> = resources.clone().collect();
+            assert_eq!(collected.root, path);
+            assert_eq!(collected.path2id, index.path2id);
+            assert_eq!(collected.id2path, index.id2path);
+
+            let explicit =
+                ResourceIndex::from_resources(path.clone(), resources);
+            assert_eq!(explicit.root, path);
+            assert_eq!(explicit.path2id, index.path2id);
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn hardlinks_should_be_grouped_apart_from_duplicates() {
+        run_test_and_clean_up(|path| {
+            let (_, original) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            let link = path.join(FILE_NAME_2);
+            std::fs::hard_link(&original, &link)
+                .expect("Should create hardlink");
+            // same content, but a different file
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_3));
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(index.collisions[&CRC32_1], 3);
+
+            let path = path.canonicalize().unwrap();
+            assert_eq!(
+                index.get_hardlink_groups(),
+                vec![vec![path.join(FILE_NAME_1), path.join(FILE_NAME_2)]]
+            );
+        })
+    }
+
+    #[test]
+    fn build_from_manifest_should_not_hash_files() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+
+            // the id doesn't match the content, proving it wasn't hashed
+            let manifest = HashMap::from([
+                (PathBuf::from(FILE_NAME_1), CRC32_2),
+                (PathBuf::from(FILE_NAME_2), CRC32_1),
+            ]);
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build_from_manifest(path.clone(), manifest);
+
+            assert_eq!(actual.size(), 1);
+            let entry = &actual.path2id[&CanonicalPathBuf::canonicalize(
+                path.join(FILE_NAME_1),
+            )
+            .unwrap()];
+            assert_eq!(entry.id, CRC32_2);
+            assert_eq!(entry.modified, SystemTime::UNIX_EPOCH);
+
+            let update = actual
+                .update_all()
+                .expect("Should update index correctly");
+            assert!(update.deleted.contains(&CRC32_2));
+            assert!(actual.id2path.contains_key(&CRC32_1));
+        })
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn build_should_skip_fifos() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), Some(FILE_NAME_1));
+            let fifo = path.join("fifo");
+            let status = std::process::Command::new("mkfifo")
+                .arg(&fifo)
+                .status()
+                .expect("Should run mkfifo");
+            assert!(status.success());
+            // a link has the size of its target path, so it isn't empty
+            std::os::unix::fs::symlink(&fifo, path.join("link"))
+                .expect("Should create symlink");
+
+            let mut actual: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            assert_eq!(actual.size(), 1);
+            assert!(actual.id2path.contains_key(&CRC32_1));
+
+            assert!(actual.update_all().unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn drain_should_empty_index() {
+        run_test_and_clean_up(|path| {
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            create_file_at(path.clone(), Some(FILE_SIZE_1), None);
+            create_file_at(path.clone(), Some(FILE_SIZE_2), None);
+
+            let mut index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+            let snapshot = index.clone();
+
+            let drained: HashMap<_, _> = index.drain().collect();
+
+            assert_eq!(drained, *snapshot.path2id);
+            assert_eq!(index.size(), 0);
+            assert!(index.id2path.is_empty());
+            assert!(index.collisions.is_empty());
+            // the clone still shares the previous maps
+            assert_eq!(snapshot.size(), 3);
+        })
+    }
+
+    #[test]
+    fn into_iter_should_yield_all_entries() {
+        run_test_and_clean_up(|path| {
+            let (_, path_1) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_1),
+                Some(FILE_NAME_1),
+            );
+            let (_, path_2) = create_file_at(
+                path.clone(),
+                Some(FILE_SIZE_2),
+                Some(FILE_NAME_2),
+            );
+
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build(path.clone());
+
+            let mut collected = Vec::new();
+            for (path, entry) in &index {
+                collected.push((path.clone(), entry.id.clone()));
+            }
+            collected.sort();
+
+            let mut expected = vec![
+                (CanonicalPathBuf::canonicalize(path_1).unwrap(), CRC32_1),
+                (CanonicalPathBuf::canonicalize(path_2).unwrap(), CRC32_2),
+            ];
+            expected.sort();
+
+            assert_eq!(collected, expected);
         })
     }
 
@@ -0,0 +1,107 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use data_error::Result;
+use data_resource::ResourceId;
+
+use crate::index::IndexEntry;
+use crate::ResourceIndex;
+
+/// A single line of the JSON Lines representation of the index
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonlEntry<Id> {
+    path: PathBuf,
+    id: Id,
+    #[serde(with = "crate::timestamp")]
+    modified: SystemTime,
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Writes one JSON object per indexed path, sorted by path,
+    /// without building the whole document in memory.
+    pub fn store_jsonl<W: Write>(&self, mut writer: W) -> Result<()> {
+        let mut paths: Vec<_> = self.path2id.iter().collect();
+        paths.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (path, entry) in paths {
+            let line = JsonlEntry {
+                path: self.relative_path(path.as_canonical_path())?,
+                id: &entry.id,
+                modified: entry.modified,
+            };
+            serde_json::to_writer(&mut writer, &line)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads an index written by [`ResourceIndex::store_jsonl`],
+    /// paths which don't exist under `root` anymore are skipped.
+    pub fn load_jsonl<R: Read>(root: &Path, reader: R) -> Result<Self> {
+        let mut entries = Vec::new();
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry: JsonlEntry<Id> = serde_json::from_str(&line)?;
+            if !root.join(&entry.path).exists() {
+                log::warn!("File {} not found", entry.path.display());
+                continue;
+            }
+
+            entries.push((
+                entry.path,
+                IndexEntry {
+                    modified: entry.modified,
+                    id: entry.id,
+                },
+            ));
+        }
+
+        Self::build_from_iter(root, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{create_file_at, run_test_and_clean_up};
+    use crate::ResourceIndex;
+    use dev_hash::Crc32;
+
+    #[test]
+    fn jsonl_should_roundtrip() {
+        run_test_and_clean_up(|dir| {
+            create_file_at(dir.clone(), Some(10), Some("test1.txt"));
+            create_file_at(dir.clone(), Some(11), Some("test2.txt"));
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let mut jsonl = Vec::new();
+            index
+                .store_jsonl(&mut jsonl)
+                .expect("Should write JSONL");
+
+            let content = String::from_utf8(jsonl.clone()).unwrap();
+            assert_eq!(content.lines().count(), 2);
+            assert!(content
+                .lines()
+                .next()
+                .unwrap()
+                .starts_with(r#"{"path":"test1.txt","id":3817498742,"#));
+
+            std::fs::remove_file(dir.join("test2.txt")).unwrap();
+            let restored: ResourceIndex<Crc32> =
+                ResourceIndex::load_jsonl(&dir, jsonl.as_slice())
+                    .expect("Should read JSONL");
+
+            assert_eq!(restored.size(), 1);
+            // timestamps are restored with full precision
+            let (path, entry) = restored.iter().next().unwrap();
+            assert_eq!(index.path2id.get(path), Some(entry));
+        })
+    }
+}
@@ -1,3 +1,21 @@
+pub mod arkignore;
+pub mod concurrent;
+pub mod diff;
 pub mod index;
+pub mod jsonl;
+pub mod lock;
+pub mod manifest;
+pub mod similarity;
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+mod timestamp;
 
-pub use index::ResourceIndex;
+pub use arkignore::{load_arkignore, ArkIgnore};
+pub use concurrent::ConcurrentResourceIndex;
+pub use diff::IndexDiff;
+pub use index::{IndexConfig, ResourceIndex};
+pub use lock::{LockEntry, LockMismatch};
+pub use snapshot::{ResourceIndexSnapshot, ResourceIndexView};
+pub use stats::IndexStatistics;
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use data_error::{ArklibError, Result};
+use data_resource::ResourceId;
+
+use crate::index::algorithm_name;
+use crate::ResourceIndex;
+
+/// A single `[[file]]` entry of a lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockEntry {
+    pub path: PathBuf,
+    pub checksum: String,
+    pub algorithm: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LockFile {
+    #[serde(default)]
+    file: Vec<LockEntry>,
+}
+
+/// A difference between the live index and a lock file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockMismatch {
+    /// The path is pinned in the lock file but is not indexed anymore
+    Missing { path: PathBuf },
+    /// The path is indexed but is not pinned in the lock file
+    Unlocked { path: PathBuf },
+    /// The content by the path doesn't match the pinned checksum
+    Changed {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+    /// The path was pinned using another hash algorithm
+    Algorithm {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Writes a TOML lock file with one `[[file]]` entry per indexed path,
+    /// sorted by path so that the output is reproducible.
+    pub fn generate_lock_file<W: Write>(&self, mut writer: W) -> Result<()> {
+        let algorithm = algorithm_name::<Id>();
+
+        let mut file = self
+            .path2id
+            .iter()
+            .map(|(path, entry)| {
+                Ok(LockEntry {
+                    path: self.relative_path(path.as_canonical_path())?,
+                    checksum: entry.id.to_string(),
+                    algorithm: algorithm.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        file.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let lock = toml::to_string(&LockFile { file })
+            .map_err(|e| ArklibError::Other(anyhow!(e)))?;
+        writer.write_all(lock.as_bytes())?;
+        Ok(())
+    }
+
+    /// Checks the index against a lock file produced by
+    /// [`ResourceIndex::generate_lock_file`] and returns every mismatch.
+    pub fn verify_lock_file<R: Read>(
+        &self,
+        mut reader: R,
+    ) -> Result<Vec<LockMismatch>> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        let lock: LockFile =
+            toml::from_str(&content).map_err(|_| ArklibError::Parse)?;
+
+        let algorithm = algorithm_name::<Id>();
+        let mut actual: HashMap<PathBuf, String> = self
+            .path2id
+            .iter()
+            .map(|(path, entry)| {
+                Ok((
+                    self.relative_path(path.as_canonical_path())?,
+                    entry.id.to_string(),
+                ))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut mismatches = Vec::new();
+        for entry in lock.file {
+            let checksum = actual.remove(&entry.path);
+            if entry.algorithm != algorithm {
+                mismatches.push(LockMismatch::Algorithm {
+                    path: entry.path,
+                    expected: entry.algorithm,
+                    actual: algorithm.clone(),
+                });
+                continue;
+            }
+
+            match checksum {
+                None => {
+                    mismatches.push(LockMismatch::Missing { path: entry.path })
+                }
+                Some(checksum) if checksum != entry.checksum => mismatches
+                    .push(LockMismatch::Changed {
+                        path: entry.path,
+                        expected: entry.checksum,
+                        actual: checksum,
+                    }),
+                Some(_) => {}
+            }
+        }
+
+        let mut unlocked: Vec<PathBuf> = actual.into_keys().collect();
+        unlocked.sort();
+        mismatches.extend(
+            unlocked
+                .into_iter()
+                .map(|path| LockMismatch::Unlocked { path }),
+        );
+
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockMismatch;
+    use crate::index::tests::{create_file_at, run_test_and_clean_up};
+    use crate::ResourceIndex;
+    use dev_hash::Crc32;
+    use std::path::PathBuf;
+
+    #[test]
+    fn lock_file_should_detect_changes() {
+        run_test_and_clean_up(|dir| {
+            create_file_at(dir.clone(), Some(10), Some("test1.txt"));
+            create_file_at(dir.clone(), Some(11), Some("test2.txt"));
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let mut lock = Vec::new();
+            index
+                .generate_lock_file(&mut lock)
+                .expect("Should generate lock file");
+
+            let content = String::from_utf8(lock.clone()).unwrap();
+            assert_eq!(content.matches("[[file]]").count(), 2);
+            assert!(content.contains("algorithm = \"crc32\""));
+
+            let mismatches = index
+                .verify_lock_file(lock.as_slice())
+                .expect("Should verify lock file");
+            assert!(mismatches.is_empty());
+
+            std::fs::remove_file(dir.join("test1.txt")).unwrap();
+            create_file_at(dir.clone(), Some(12), Some("test2.txt"));
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+
+            let mismatches = index
+                .verify_lock_file(lock.as_slice())
+                .expect("Should verify lock file");
+
+            assert_eq!(mismatches.len(), 2);
+            assert!(mismatches.contains(&LockMismatch::Missing {
+                path: PathBuf::from("test1.txt")
+            }));
+            assert!(mismatches.iter().any(|mismatch| matches!(
+                mismatch,
+                LockMismatch::Changed { path, .. } if path == &PathBuf::from("test2.txt")
+            )));
+        })
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use data_error::{ArklibError, Result};
+use data_resource::ResourceId;
+
+use crate::index::IndexEntry;
+use crate::ResourceIndex;
+
+// same separator as in `sha256sum` output
+const SEPARATOR: &str = "  ";
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Writes one `<id>  <relative path>` line per indexed path,
+    /// sorted by path, in the format of `sha256sum`-like tools.
+    pub fn as_manifest<W: Write>(&self, mut writer: W) -> Result<()> {
+        let mut lines = self
+            .path2id
+            .iter()
+            .map(|(path, entry)| {
+                Ok((self.relative_path(path.as_canonical_path())?, &entry.id))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        lines.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (path, id) in lines {
+            writeln!(writer, "{}{}{}", id, SEPARATOR, path.display())?;
+        }
+        Ok(())
+    }
+
+    /// Builds an index from a manifest written by
+    /// [`ResourceIndex::as_manifest`], every listed path must exist
+    /// under `root`. Ids are taken from the manifest without re-hashing.
+    /// To build an index from ids already held in memory, skipping
+    /// missing paths, see [`ResourceIndex::build_from_manifest`].
+    pub fn from_manifest<R: Read>(root: &Path, reader: R) -> Result<Self> {
+        let entries = BufReader::new(reader)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
+            .map(|line| {
+                let line = line?;
+                let (id, path) = line
+                    .split_once(SEPARATOR)
+                    .ok_or(ArklibError::Parse)?;
+                let id = Id::from_str(id).map_err(|_| ArklibError::Parse)?;
+                let path = PathBuf::from(path);
+
+                let modified = fs::metadata(root.join(&path))
+                    .map_err(|_| ArklibError::ResourceNotFound(path.clone()))?
+                    .modified()?;
+                Ok((path, IndexEntry { modified, id }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::build_from_iter(root, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{create_file_at, run_test_and_clean_up};
+    use crate::ResourceIndex;
+    use data_error::ArklibError;
+    use dev_hash::Crc32;
+
+    #[test]
+    fn manifest_should_roundtrip() {
+        run_test_and_clean_up(|dir| {
+            create_file_at(dir.clone(), Some(10), Some("test1.txt"));
+            create_file_at(dir.clone(), Some(11), Some("test 2.txt"));
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let mut manifest = Vec::new();
+            index
+                .as_manifest(&mut manifest)
+                .expect("Should write manifest");
+
+            let content = String::from_utf8(manifest.clone()).unwrap();
+            assert_eq!(
+                content,
+                "1804055020  test 2.txt\n3817498742  test1.txt\n"
+            );
+
+            let restored: ResourceIndex<Crc32> =
+                ResourceIndex::from_manifest(&dir, manifest.as_slice())
+                    .expect("Should read manifest");
+            assert_eq!(restored.path2id, index.path2id);
+
+            let missing: Result<ResourceIndex<Crc32>, _> =
+                ResourceIndex::from_manifest(
+                    &dir,
+                    "1  missing.txt\n".as_bytes(),
+                );
+            assert!(matches!(missing, Err(ArklibError::ResourceNotFound(_))));
+        })
+    }
+}
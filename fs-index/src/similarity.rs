@@ -0,0 +1,251 @@
+use canonical_path::CanonicalPathBuf;
+use rayon::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use data_resource::ResourceId;
+
+use crate::index::IndexEntry;
+use crate::{IndexConfig, ResourceIndex};
+
+/// Number of hash functions in a MinHash signature
+pub const MINHASH_SIZE: usize = 128;
+
+/// Number of consecutive words in a shingle
+const SHINGLE_SIZE: usize = 3;
+
+// only the beginning of larger texts is shingled
+const MAX_SHINGLED_BYTES: u64 = 1024 * 1024;
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Finds pairs of text files with similar content.
+    ///
+    /// Only files with an extension listed in
+    /// [`IndexConfig::shingle_text_extensions`](crate::IndexConfig) are
+    /// compared, empty ones are skipped. Similarity is the Jaccard similarity
+    /// of word shingles, estimated from the MinHash signatures of the
+    /// entries. Pairs with similarity of at least `threshold` are returned,
+    /// most similar first.
+    pub fn find_similar_text_files(
+        &self,
+        threshold: f32,
+    ) -> Vec<(PathBuf, PathBuf, f32)> {
+        let mut signatures: Vec<(PathBuf, Vec<u32>)> = self
+            .path2id
+            .iter()
+            .filter_map(|(path, entry)| {
+                // signatures aren't stored with the index,
+                // so loaded entries are read again
+                let signature = match self.signatures.get(path) {
+                    Some((id, signature)) if id == &entry.id => {
+                        Some(signature.clone())
+                    }
+                    _ => text_signature(path.as_path(), &self.config),
+                }?;
+                Some((path.as_path().to_path_buf(), signature))
+            })
+            .collect();
+        signatures.sort();
+
+        let mut similar = Vec::new();
+        for (i, (path_a, signature_a)) in signatures.iter().enumerate() {
+            for (path_b, signature_b) in signatures.iter().skip(i + 1) {
+                let similarity = similarity(signature_a, signature_b);
+                if similarity >= threshold {
+                    similar.push((path_a.clone(), path_b.clone(), similarity));
+                }
+            }
+        }
+
+        similar.sort_by(|a, b| b.2.total_cmp(&a.2));
+        similar
+    }
+}
+
+// the signature of a text file, `None` for other files and for texts
+// without any words, which would otherwise be equal to each other
+pub(crate) fn text_signature(
+    path: &Path,
+    config: &IndexConfig,
+) -> Option<Vec<u32>> {
+    let is_text = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            config
+                .shingle_text_extensions
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(ext))
+        })
+        .unwrap_or(false);
+    if !is_text {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let read = File::open(path).and_then(|file| {
+        file.take(MAX_SHINGLED_BYTES)
+            .read_to_end(&mut bytes)
+    });
+    match read {
+        Ok(_) => minhash_signature(&String::from_utf8_lossy(&bytes)),
+        Err(e) => {
+            log::warn!("Couldn't read {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+// signatures of the text files among freshly scanned entries,
+// computed in parallel and paired with the id of the entry
+pub(crate) fn text_signatures<Id>(
+    entries: &HashMap<CanonicalPathBuf, IndexEntry<Id>>,
+    config: &IndexConfig,
+) -> HashMap<CanonicalPathBuf, (Id, Vec<u32>)>
+where
+    Id: ResourceId + Send + Sync,
+{
+    if config.shingle_text_extensions.is_empty() {
+        return HashMap::new();
+    }
+
+    entries
+        .par_iter()
+        .filter_map(|(path, entry)| {
+            let signature = text_signature(path.as_path(), config)?;
+            Some((path.clone(), (entry.id.clone(), signature)))
+        })
+        .collect()
+}
+
+/// Computes the MinHash signature of the word shingles of `text`,
+/// `None` if there are no words
+pub fn minhash_signature(text: &str) -> Option<Vec<u32>> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    let shingles = words.windows(SHINGLE_SIZE.min(words.len()));
+
+    let mut signature = vec![u32::MAX; MINHASH_SIZE];
+    for shingle in shingles {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // each of the hash functions is a cheap permutation
+        // of the base hash seeded by its position
+        let mut seed = 0u64;
+        for min in signature.iter_mut() {
+            seed = splitmix64(seed);
+            let permuted = (splitmix64(hash ^ seed) >> 32) as u32;
+            *min = (*min).min(permuted);
+        }
+    }
+
+    Some(signature)
+}
+
+fn similarity(a: &[u32], b: &[u32]) -> f32 {
+    let equal = a.iter().zip(b).filter(|(a, b)| a == b).count();
+    equal as f32 / MINHASH_SIZE as f32
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::run_test_and_clean_up;
+    use crate::{IndexConfig, ResourceIndex};
+    use dev_hash::Crc32;
+    use std::time::SystemTime;
+
+    const TEXT: &str = "the quick brown fox jumps over the lazy dog \
+        while the cat sleeps on the warm mat near the old fireplace \
+        and the birds sing outside the window every single morning";
+
+    #[test]
+    fn should_find_near_duplicate_text_files() {
+        run_test_and_clean_up(|dir| {
+            std::fs::write(dir.join("a.txt"), TEXT).unwrap();
+            std::fs::write(dir.join("b.txt"), format!("{} today", TEXT))
+                .unwrap();
+            std::fs::write(dir.join("c.txt"), "something entirely different")
+                .unwrap();
+            std::fs::write(dir.join("d.md"), TEXT).unwrap();
+
+            let config = IndexConfig {
+                shingle_text_extensions: vec!["txt".to_string()],
+                ..Default::default()
+            };
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(&dir, config);
+            let similar = index.find_similar_text_files(0.8);
+
+            assert_eq!(similar.len(), 1);
+            let (a, b, similarity) = &similar[0];
+            assert!(a.ends_with("a.txt"));
+            assert!(b.ends_with("b.txt"));
+            assert!(*similarity >= 0.8 && *similarity < 1.0);
+        })
+    }
+
+    #[test]
+    fn should_skip_texts_without_words() {
+        run_test_and_clean_up(|dir| {
+            std::fs::write(dir.join("a.txt"), " \n").unwrap();
+            std::fs::write(dir.join("b.txt"), "\n\n\t").unwrap();
+            std::fs::write(dir.join("c.txt"), TEXT).unwrap();
+
+            let config = IndexConfig {
+                shingle_text_extensions: vec!["txt".to_string()],
+                ..Default::default()
+            };
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(&dir, config);
+            let similar = index.find_similar_text_files(0.0);
+
+            assert!(similar.is_empty());
+            assert_eq!(index.signatures.len(), 1);
+        })
+    }
+
+    #[test]
+    fn should_equal_the_loaded_index_with_signatures() {
+        run_test_and_clean_up(|dir| {
+            std::fs::write(dir.join("a.txt"), TEXT).unwrap();
+            // the index is stored with millisecond precision
+            std::fs::File::options()
+                .write(true)
+                .open(dir.join("a.txt"))
+                .and_then(|file| file.set_modified(SystemTime::UNIX_EPOCH))
+                .unwrap();
+
+            let config = IndexConfig {
+                shingle_text_extensions: vec!["txt".to_string()],
+                ..Default::default()
+            };
+            let index: ResourceIndex<Crc32> =
+                ResourceIndex::build_with_config(&dir, config.clone());
+            index.store().unwrap();
+            let loaded: ResourceIndex<Crc32> =
+                ResourceIndex::load_with_config(&dir, config).unwrap();
+
+            assert_eq!(index.signatures.len(), 1);
+            assert!(loaded.signatures.is_empty());
+            assert_eq!(index, loaded);
+        })
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{hash_map, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use canonical_path::CanonicalPathBuf;
+
+use data_resource::ResourceId;
+
+use crate::index::{EntryRef, IndexEntry};
+use crate::ResourceIndex;
+
+/// Read-only query API shared by [`ResourceIndex`] and
+/// [`ResourceIndexSnapshot`].
+///
+/// Implementors only expose their maps, the queries are implemented
+/// here once on top of them.
+pub trait ResourceIndexView<Id: ResourceId> {
+    fn root(&self) -> &Path;
+    fn id2path(&self) -> &HashMap<Id, CanonicalPathBuf>;
+    fn path2id(&self) -> &HashMap<CanonicalPathBuf, IndexEntry<Id>>;
+    fn collisions(&self) -> &HashMap<Id, usize>;
+    // paths of the ids in `collisions`
+    fn collided_paths(&self) -> &HashMap<Id, HashSet<CanonicalPathBuf>>;
+
+    fn size(&self) -> usize {
+        //the actual size is lower in presence of collisions
+        self.path2id().len()
+    }
+
+    fn len_unique(&self) -> usize {
+        // number of distinct ids, lower than `size` in presence of collisions
+        self.id2path().len()
+    }
+
+    // `path` can be either relative to the root of the index
+    // or absolute, it is canonicalized so it must exist
+    fn get_resource_by_path<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<&IndexEntry<Id>> {
+        let path = resolve_path(self.root(), path)?;
+        self.path2id().get(&path)
+    }
+
+    // same rules for `path` as in `get_resource_by_path`
+    fn contains_path(&self, path: &Path) -> bool {
+        resolve_path(self.root(), path)
+            .map(|path| self.path2id().contains_key(&path))
+            .unwrap_or(false)
+    }
+
+    fn contains_id(&self, id: &Id) -> bool {
+        self.id2path().contains_key(id)
+    }
+
+    // one of the entries with the given id, without
+    // looking for the other paths in case of collisions
+    fn get_resource_by_id_any(&self, id: &Id) -> Option<EntryRef<'_, Id>> {
+        let path = self.id2path().get(id)?;
+        self.path2id().get_key_value(path)
+    }
+
+    // paths of every id found more than once
+    fn collision_groups(
+        &self,
+    ) -> hash_map::Iter<'_, Id, HashSet<CanonicalPathBuf>> {
+        self.collided_paths().iter()
+    }
+
+    // number of indexed paths under `relative_subdir`,
+    // which is relative to the root of the index
+    fn len_in_subtree(&self, relative_subdir: &Path) -> usize {
+        self.path2id()
+            .keys()
+            .filter(|path| is_in_subtree(self.root(), path, relative_subdir))
+            .count()
+    }
+
+    // entries under `relative_subdir`, an empty path selects all of them
+    fn resources_in_subtree(
+        &self,
+        relative_subdir: &Path,
+    ) -> Vec<EntryRef<'_, Id>> {
+        self.path2id()
+            .iter()
+            .filter(|(path, _)| {
+                is_in_subtree(self.root(), path, relative_subdir)
+            })
+            .collect()
+    }
+}
+
+fn resolve_path<P: AsRef<Path>>(
+    root: &Path,
+    path: P,
+) -> Option<CanonicalPathBuf> {
+    CanonicalPathBuf::canonicalize(root.join(path)).ok()
+}
+
+fn is_in_subtree(
+    root: &Path,
+    path: &CanonicalPathBuf,
+    relative_subdir: &Path,
+) -> bool {
+    pathdiff::diff_paths(path.as_path(), root)
+        .map(|path| path.starts_with(relative_subdir))
+        .unwrap_or(false)
+}
+
+/// A frozen copy of a [`ResourceIndex`].
+///
+/// The snapshot shares the internal maps with the index it was taken from,
+/// so taking it is cheap. The index copies a map only when it modifies it
+/// while a snapshot is still alive, so the snapshot can be moved to another
+/// thread and queried while the index itself keeps being updated.
+#[derive(PartialEq, Clone, Debug)]
+pub struct ResourceIndexSnapshot<Id: ResourceId> {
+    id2path: Arc<HashMap<Id, CanonicalPathBuf>>,
+    path2id: Arc<HashMap<CanonicalPathBuf, IndexEntry<Id>>>,
+    collisions: Arc<HashMap<Id, usize>>,
+    collided_paths: Arc<HashMap<Id, HashSet<CanonicalPathBuf>>>,
+    root: Arc<Path>,
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    pub fn snapshot(&self) -> ResourceIndexSnapshot<Id> {
+        ResourceIndexSnapshot {
+            id2path: self.id2path.clone(),
+            path2id: self.path2id.clone(),
+            collisions: self.collisions.clone(),
+            collided_paths: self.collided_paths.clone(),
+            root: Arc::from(self.root.as_path()),
+        }
+    }
+}
+
+impl<Id: ResourceId> ResourceIndexView<Id> for ResourceIndex<Id> {
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn id2path(&self) -> &HashMap<Id, CanonicalPathBuf> {
+        &self.id2path
+    }
+
+    fn path2id(&self) -> &HashMap<CanonicalPathBuf, IndexEntry<Id>> {
+        &self.path2id
+    }
+
+    fn collisions(&self) -> &HashMap<Id, usize> {
+        &self.collisions
+    }
+
+    fn collided_paths(&self) -> &HashMap<Id, HashSet<CanonicalPathBuf>> {
+        &self.collided_paths
+    }
+}
+
+impl<Id: ResourceId> ResourceIndexView<Id> for ResourceIndexSnapshot<Id> {
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn id2path(&self) -> &HashMap<Id, CanonicalPathBuf> {
+        &self.id2path
+    }
+
+    fn path2id(&self) -> &HashMap<CanonicalPathBuf, IndexEntry<Id>> {
+        &self.path2id
+    }
+
+    fn collisions(&self) -> &HashMap<Id, usize> {
+        &self.collisions
+    }
+
+    fn collided_paths(&self) -> &HashMap<Id, HashSet<CanonicalPathBuf>> {
+        &self.collided_paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResourceIndexView;
+    use crate::index::tests::{
+        create_dir, create_file_at, run_test_and_clean_up, CRC32_1, CRC32_2,
+    };
+    use crate::ResourceIndex;
+    use dev_hash::Crc32;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    #[test]
+    fn snapshot_should_not_observe_later_updates() {
+        run_test_and_clean_up(|dir| {
+            create_file_at(dir.clone(), Some(10), Some("test1.txt"));
+
+            let mut index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let snapshot = index.snapshot();
+            assert!(Arc::ptr_eq(&snapshot.path2id, &index.path2id));
+            assert_eq!(ResourceIndexView::size(&snapshot), 1);
+
+            create_file_at(dir.clone(), Some(11), Some("test2.txt"));
+            index.update_all().expect("Should update index");
+
+            assert_eq!(index.size(), 2);
+            assert_eq!(snapshot.size(), 1);
+            assert_eq!(snapshot.len_unique(), 1);
+            assert_eq!(snapshot.root(), dir.as_path());
+        })
+    }
+
+    #[test]
+    fn snapshot_should_answer_queries_like_index() {
+        run_test_and_clean_up(|dir| {
+            let nested = create_dir(
+                &dir,
+                "nested",
+                &[("test1.txt", 10), ("test2.txt", 10)],
+            );
+            create_file_at(dir.clone(), Some(11), Some("test3.txt"));
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let snapshot = index.snapshot();
+
+            let entry = snapshot
+                .get_resource_by_path("test3.txt")
+                .expect("Should find the resource by relative path");
+            assert_eq!(entry.id, CRC32_2);
+            assert!(snapshot.contains_path(&nested.join("test1.txt")));
+            assert!(snapshot.contains_id(&CRC32_1));
+            assert!(snapshot
+                .get_resource_by_id_any(&CRC32_2)
+                .is_some());
+            assert_eq!(snapshot.collision_groups().count(), 1);
+            assert_eq!(snapshot.len_in_subtree(Path::new("nested")), 2);
+            assert_eq!(
+                snapshot.resources_in_subtree(Path::new("")).len(),
+                index.size()
+            );
+        })
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use rusqlite::{params, Connection};
+
+use data_error::{ArklibError, Result};
+use data_resource::ResourceId;
+
+use crate::index::IndexEntry;
+use crate::ResourceIndex;
+
+const SCHEMA: &str = "
+    DROP TABLE IF EXISTS resources;
+    DROP TABLE IF EXISTS collisions;
+    CREATE TABLE resources (
+        path TEXT PRIMARY KEY,
+        id TEXT NOT NULL,
+        last_modified INTEGER NOT NULL,
+        size_bytes INTEGER
+    );
+    CREATE TABLE collisions (
+        id TEXT NOT NULL,
+        path TEXT NOT NULL
+    );
+";
+
+fn sqlite_error(err: rusqlite::Error) -> ArklibError {
+    ArklibError::Other(err.into())
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    /// Writes the index into the `resources` and `collisions` tables
+    /// of a SQLite database, replacing their previous content.
+    /// Paths are relative to the root and timestamps are in milliseconds.
+    pub fn export_to_sqlite<P: AsRef<Path>>(&self, db_path: P) -> Result<()> {
+        let mut connection = Connection::open(db_path).map_err(sqlite_error)?;
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        transaction
+            .execute_batch(SCHEMA)
+            .map_err(sqlite_error)?;
+
+        {
+            let mut insert_resource = transaction
+                .prepare(
+                    "INSERT INTO resources \
+                    (path, id, last_modified, size_bytes) \
+                    VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(sqlite_error)?;
+            let mut insert_collision = transaction
+                .prepare("INSERT INTO collisions (id, path) VALUES (?1, ?2)")
+                .map_err(sqlite_error)?;
+
+            for (path, entry) in self.path2id.iter() {
+                let relative = self.relative_path(path.as_canonical_path())?;
+                let relative = relative.to_string_lossy();
+                let id = entry.id.to_string();
+                let modified = entry
+                    .modified
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(|_| {
+                        ArklibError::Other(anyhow!(
+                            "Error using duration since"
+                        ))
+                    })?
+                    .as_millis() as i64;
+                let size = fs::metadata(path)
+                    .ok()
+                    .map(|metadata| metadata.len() as i64);
+
+                insert_resource
+                    .execute(params![relative, id, modified, size])
+                    .map_err(sqlite_error)?;
+                if self.collisions.contains_key(&entry.id) {
+                    insert_collision
+                        .execute(params![id, relative])
+                        .map_err(sqlite_error)?;
+                }
+            }
+        }
+
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    /// Reads the `resources` table written by
+    /// [`ResourceIndex::export_to_sqlite`], paths which don't exist
+    /// under `root` anymore are skipped.
+    pub fn from_sqlite<P: AsRef<Path>>(
+        root: &Path,
+        db_path: P,
+    ) -> Result<Self> {
+        let connection = Connection::open(db_path).map_err(sqlite_error)?;
+        let mut statement = connection
+            .prepare("SELECT path, id, last_modified FROM resources")
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })
+            .map_err(sqlite_error)?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (path, id, modified) = row.map_err(sqlite_error)?;
+            let path = PathBuf::from(path);
+            if !root.join(&path).exists() {
+                log::warn!("File {} not found", path.display());
+                continue;
+            }
+
+            let id = Id::from_str(&id).map_err(|_| ArklibError::Parse)?;
+            // `export_to_sqlite` never writes negative or overflowing ones
+            let modified = u64::try_from(modified)
+                .ok()
+                .and_then(|millis| {
+                    UNIX_EPOCH.checked_add(Duration::from_millis(millis))
+                })
+                .ok_or(ArklibError::Parse)?;
+            entries.push((path, IndexEntry { modified, id }));
+        }
+
+        Self::build_from_iter(root, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{create_dir, run_test_and_clean_up};
+    use crate::ResourceIndex;
+    use data_error::ArklibError;
+    use dev_hash::Crc32;
+    use rusqlite::Connection;
+    use std::fs::File;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn export_to_sqlite_should_fill_tables() {
+        run_test_and_clean_up(|path| {
+            let dir = create_dir(
+                &path,
+                "root",
+                &[("a.txt", 10), ("b.txt", 10), ("c.txt", 11)],
+            );
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let db_path = path.join("index.sqlite");
+            index
+                .export_to_sqlite(&db_path)
+                .expect("Should export index");
+            // exporting again replaces the previous content
+            index
+                .export_to_sqlite(&db_path)
+                .expect("Should export index");
+
+            let connection = Connection::open(&db_path).unwrap();
+            let (count, size): (i64, i64) = connection
+                .query_row(
+                    "SELECT COUNT(*), SUM(size_bytes) FROM resources",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .unwrap();
+            let mut statement = connection
+                .prepare("SELECT path FROM collisions ORDER BY path")
+                .unwrap();
+            let collisions: Vec<String> = statement
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert_eq!(count, 3);
+            assert_eq!(size, 31);
+            assert_eq!(collisions, vec!["a.txt", "b.txt"]);
+        })
+    }
+
+    #[test]
+    fn from_sqlite_should_roundtrip() {
+        run_test_and_clean_up(|path| {
+            let dir = create_dir(
+                &path,
+                "root",
+                &[("a.txt", 10), ("b.txt", 10), ("c.txt", 11)],
+            );
+            // timestamps are exported in milliseconds
+            let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+            for name in ["a.txt", "b.txt", "c.txt"] {
+                File::options()
+                    .write(true)
+                    .open(dir.join(name))
+                    .and_then(|file| file.set_modified(modified))
+                    .expect("Could not set timestamp");
+            }
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let db_path = path.join("index.sqlite");
+            index
+                .export_to_sqlite(&db_path)
+                .expect("Should export index");
+
+            let restored: ResourceIndex<Crc32> =
+                ResourceIndex::from_sqlite(&dir, &db_path)
+                    .expect("Should import index");
+
+            // `id2path` keeps an arbitrary one of the colliding paths
+            assert_eq!(restored.root, index.root);
+            assert_eq!(restored.path2id, index.path2id);
+            assert_eq!(restored.collisions, index.collisions);
+        })
+    }
+
+    #[test]
+    fn from_sqlite_should_reject_negative_timestamps() {
+        run_test_and_clean_up(|path| {
+            let dir = create_dir(&path, "root", &[("a.txt", 10)]);
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let db_path = path.join("index.sqlite");
+            index
+                .export_to_sqlite(&db_path)
+                .expect("Should export index");
+
+            Connection::open(&db_path)
+                .unwrap()
+                .execute("UPDATE resources SET last_modified = -1", [])
+                .unwrap();
+
+            assert!(matches!(
+                ResourceIndex::<Crc32>::from_sqlite(&dir, &db_path),
+                Err(ArklibError::Parse)
+            ));
+        })
+    }
+}
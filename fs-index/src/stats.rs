@@ -0,0 +1,62 @@
+use std::fs;
+
+use data_resource::ResourceId;
+
+use crate::index::algorithm_name;
+use crate::ResourceIndex;
+
+/// Summary of an index, see [`ResourceIndex::statistics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexStatistics {
+    pub total_files: usize,
+    /// Sum of the sizes of indexed files which are still present on disk
+    pub total_size: u64,
+    pub unique_ids: usize,
+    pub collision_groups: usize,
+    /// Lowercased name of the `ResourceId` type, e.g. "crc32"
+    pub algorithm: String,
+}
+
+impl<Id: ResourceId> ResourceIndex<Id> {
+    pub fn statistics(&self) -> IndexStatistics {
+        let total_size = self
+            .path2id
+            .keys()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        IndexStatistics {
+            total_files: self.size(),
+            total_size,
+            unique_ids: self.len_unique(),
+            collision_groups: self.collisions.len(),
+            algorithm: algorithm_name::<Id>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::tests::{create_file_at, run_test_and_clean_up};
+    use crate::ResourceIndex;
+    use dev_hash::Crc32;
+
+    #[test]
+    fn statistics_should_summarize_index() {
+        run_test_and_clean_up(|dir| {
+            for (name, size) in [("a.txt", 10), ("b.txt", 10), ("c.txt", 11)] {
+                create_file_at(dir.clone(), Some(size), Some(name));
+            }
+
+            let index: ResourceIndex<Crc32> = ResourceIndex::build(&dir);
+            let stats = index.statistics();
+
+            assert_eq!(stats.total_files, 3);
+            assert_eq!(stats.total_size, 31);
+            assert_eq!(stats.unique_ids, 2);
+            assert_eq!(stats.collision_groups, 1);
+            assert_eq!(stats.algorithm, "crc32");
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! Lossless serde representation of `SystemTime`, to be used with
+//! `#[serde(with = "crate::timestamp")]`. Times before the Unix epoch
+//! have negative seconds, nanoseconds are always counted forward.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Timestamp {
+    secs: i64,
+    nanos: u32,
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => Timestamp {
+                secs: after.as_secs() as i64,
+                nanos: after.subsec_nanos(),
+            },
+            Err(err) => {
+                let before = err.duration();
+                let secs = -(before.as_secs() as i64);
+                match before.subsec_nanos() {
+                    0 => Timestamp { secs, nanos: 0 },
+                    nanos => Timestamp {
+                        secs: secs - 1,
+                        nanos: NANOS_PER_SEC - nanos,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl Timestamp {
+    // `None` if the time is out of the range of `SystemTime`,
+    // which is narrower than `i64` seconds on some platforms
+    fn to_system_time(self) -> Option<SystemTime> {
+        let nanos = Duration::from_nanos(self.nanos as u64);
+        let secs = if self.secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(self.secs as u64))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(self.secs.unsigned_abs()))
+        };
+        secs?.checked_add(nanos)
+    }
+}
+
+pub fn serialize<S: Serializer>(
+    time: &SystemTime,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Timestamp::from(*time).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SystemTime, D::Error> {
+    let timestamp = Timestamp::deserialize(deserializer)?;
+    if timestamp.nanos >= NANOS_PER_SEC {
+        return Err(serde::de::Error::custom(
+            "nanoseconds must be less than a second",
+        ));
+    }
+    timestamp.to_system_time().ok_or_else(|| {
+        serde::de::Error::custom("timestamp is out of the supported range")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "crate::timestamp")] SystemTime);
+
+    fn roundtrip(time: SystemTime) -> SystemTime {
+        let json = serde_json::to_string(&Wrapper(time)).unwrap();
+        serde_json::from_str::<Wrapper>(&json).unwrap().0
+    }
+
+    proptest! {
+        #[test]
+        fn timestamp_should_roundtrip(
+            secs in -10_000_000_000i64..10_000_000_000i64,
+            nanos in 0u32..1_000_000_000,
+        ) {
+            let offset = Duration::from_secs(secs.unsigned_abs());
+            let time = if secs >= 0 {
+                UNIX_EPOCH + offset
+            } else {
+                UNIX_EPOCH - offset
+            } + Duration::from_nanos(nanos as u64);
+
+            prop_assert_eq!(roundtrip(time), time);
+        }
+    }
+
+    #[test]
+    fn timestamp_should_count_nanos_forward_before_epoch() {
+        let time = UNIX_EPOCH - Duration::from_millis(1500);
+        let json = serde_json::to_string(&Wrapper(time)).unwrap();
+
+        assert_eq!(json, r#"{"secs":-2,"nanos":500000000}"#);
+        assert!(serde_json::from_str::<Wrapper>(
+            r#"{"secs":0,"nanos":1000000000}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn timestamp_out_of_range_should_fail_without_panicking() {
+        let json = format!(r#"{{"secs":{},"nanos":0}}"#, i64::MAX);
+        let result = serde_json::from_str::<Wrapper>(&json);
+
+        // representable on some platforms only
+        let expected =
+            UNIX_EPOCH.checked_add(Duration::from_secs(i64::MAX as u64));
+        assert_eq!(result.ok().map(|wrapper| wrapper.0), expected);
+    }
+}
@@ -6,7 +6,7 @@ use std::io::Read;
 use std::path::Path;
 
 use data_resource::ResourceId;
-use fs_storage::ARK_FOLDER;
+use fs_storage::ark_folder_path;
 
 pub const METADATA_STORAGE_FOLDER: &str = "cache/metadata";
 
@@ -20,8 +20,7 @@ pub fn store_metadata<
     metadata: &S,
 ) -> Result<()> {
     let file = AtomicFile::new(
-        root.as_ref()
-            .join(ARK_FOLDER)
+        ark_folder_path(root.as_ref())
             .join(METADATA_STORAGE_FOLDER)
             .join(id.to_string()),
     )?;
@@ -47,9 +46,7 @@ pub fn load_raw_metadata<P: AsRef<Path>, Id: ResourceId>(
     root: P,
     id: Id,
 ) -> Result<Vec<u8>> {
-    let storage = root
-        .as_ref()
-        .join(ARK_FOLDER)
+    let storage = ark_folder_path(root.as_ref())
         .join(METADATA_STORAGE_FOLDER)
         .join(id.to_string());
     let file = AtomicFile::new(storage)?;
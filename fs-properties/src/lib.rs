@@ -8,7 +8,7 @@ use data_error::Result;
 use data_json::merge;
 use data_resource::ResourceId;
 use fs_atomic_versions::atomic::{modify_json, AtomicFile};
-use fs_storage::ARK_FOLDER;
+use fs_storage::ark_folder_path;
 
 pub const PROPERTIES_STORAGE_FOLDER: &str = "user/properties";
 
@@ -22,8 +22,7 @@ pub fn store_properties<
     properties: &S,
 ) -> Result<()> {
     let file = AtomicFile::new(
-        root.as_ref()
-            .join(ARK_FOLDER)
+        ark_folder_path(root.as_ref())
             .join(PROPERTIES_STORAGE_FOLDER)
             .join(id.to_string()),
     )?;
@@ -47,9 +46,7 @@ pub fn load_raw_properties<P: AsRef<Path>, Id: ResourceId>(
     root: P,
     id: Id,
 ) -> Result<Vec<u8>> {
-    let storage = root
-        .as_ref()
-        .join(ARK_FOLDER)
+    let storage = ark_folder_path(root.as_ref())
         .join(PROPERTIES_STORAGE_FOLDER)
         .join(id.to_string());
     let file = AtomicFile::new(storage)?;
@@ -1,10 +1,24 @@
+//! Storage primitives and the layout of the `.ark` folder.
+//!
+//! The `.ark` folder lives in the root of every indexed directory.
+//! Its location can be overridden with the `ARK_FOLDER` environment
+//! variable, e.g. to redirect it to a scratch space in CI. Relative values
+//! are resolved against the root and must stay inside of it, so `..` is
+//! rejected. Under an absolute value every root gets its own subfolder,
+//! named by a hash of the canonical root. See [`ark_folder_path`].
+
 pub mod base_storage;
 pub mod file_storage;
 #[cfg(feature = "jni-bindings")]
 pub mod jni;
 pub mod monoid;
 mod utils;
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
 pub const ARK_FOLDER: &str = ".ark";
+pub const ARK_FOLDER_ENV: &str = "ARK_FOLDER";
 
 // Should not be lost if possible
 pub const STATS_FOLDER: &str = "stats";
@@ -18,3 +32,142 @@ pub const SCORE_STORAGE_FILE: &str = "user/scores";
 pub const INDEX_PATH: &str = "index";
 pub const PREVIEWS_STORAGE_FOLDER: &str = "cache/previews";
 pub const THUMBNAILS_STORAGE_FOLDER: &str = "cache/thumbnails";
+
+/// Returns the `.ark` folder of `root`, respecting
+/// the `ARK_FOLDER` environment variable
+pub fn ark_folder_path(root: &Path) -> PathBuf {
+    resolve_ark_folder(root, std::env::var_os(ARK_FOLDER_ENV))
+}
+
+/// Returns the root whose `.ark` folder is `ark_folder`, the reverse
+/// of [`ark_folder_path`]. Under an absolute `ARK_FOLDER` the root
+/// isn't a parent of its folder, so it can't be found
+pub fn ark_folder_root(ark_folder: &Path) -> Option<PathBuf> {
+    resolve_ark_folder_root(ark_folder, std::env::var_os(ARK_FOLDER_ENV))
+}
+
+fn resolve_ark_folder(root: &Path, folder: Option<OsString>) -> PathBuf {
+    let folder = match folder {
+        Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => return root.join(ARK_FOLDER),
+    };
+
+    if folder
+        .components()
+        .any(|component| component == Component::ParentDir)
+    {
+        log::warn!(
+            "Ignoring {}={}, it must not contain `..`",
+            ARK_FOLDER_ENV,
+            folder.display()
+        );
+        return root.join(ARK_FOLDER);
+    }
+
+    if folder.is_absolute() {
+        return folder.join(root_hash(root));
+    }
+    root.join(folder)
+}
+
+fn resolve_ark_folder_root(
+    ark_folder: &Path,
+    folder: Option<OsString>,
+) -> Option<PathBuf> {
+    ark_folder
+        .ancestors()
+        .skip(1)
+        .find(|root| resolve_ark_folder(root, folder.clone()) == ark_folder)
+        .map(Path::to_path_buf)
+}
+
+// names the folder of `root` inside of a shared absolute `ARK_FOLDER`,
+// the same root given by different paths gets the same folder
+fn root_hash(root: &Path) -> String {
+    let root = root
+        .canonicalize()
+        .unwrap_or_else(|_| root.to_path_buf());
+    let hash = blake3::hash(root.to_string_lossy().as_bytes());
+    hash.to_hex()[..32].to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ark_folder_should_be_resolved_against_root() {
+        let root = Path::new("/tmp/root");
+
+        assert_eq!(resolve_ark_folder(root, None), root.join(".ark"));
+        assert_eq!(
+            resolve_ark_folder(root, Some("".into())),
+            root.join(".ark")
+        );
+        assert_eq!(
+            resolve_ark_folder(root, Some("scratch".into())),
+            root.join("scratch")
+        );
+    }
+
+    #[test]
+    fn absolute_ark_folder_should_be_separate_for_every_root() {
+        let first = resolve_ark_folder(
+            Path::new("/tmp/first"),
+            Some("/var/scratch".into()),
+        );
+        let second = resolve_ark_folder(
+            Path::new("/tmp/second"),
+            Some("/var/scratch".into()),
+        );
+
+        assert!(first.starts_with("/var/scratch"));
+        assert!(second.starts_with("/var/scratch"));
+        assert_ne!(first, second);
+        assert_eq!(
+            resolve_ark_folder(
+                Path::new("/tmp/first"),
+                Some("/var/scratch".into())
+            ),
+            first
+        );
+    }
+
+    #[test]
+    fn ark_folder_root_should_reverse_ark_folder() {
+        let root = Path::new("/tmp/root");
+
+        for folder in [None, Some("scratch"), Some("nested/scratch")] {
+            let folder = folder.map(OsString::from);
+            let ark_folder = resolve_ark_folder(root, folder.clone());
+            assert_eq!(
+                resolve_ark_folder_root(&ark_folder, folder),
+                Some(root.to_path_buf())
+            );
+        }
+
+        // the root can't be derived from a folder named by its hash,
+        // and no parent of the folder must be mistaken for it
+        let folder = Some(OsString::from("/var/scratch"));
+        let ark_folder = resolve_ark_folder(root, folder.clone());
+        assert_eq!(resolve_ark_folder_root(&ark_folder, folder), None);
+    }
+
+    #[test]
+    fn ark_folder_should_not_escape_root() {
+        let root = Path::new("/tmp/root");
+
+        assert_eq!(
+            resolve_ark_folder(root, Some("../scratch".into())),
+            root.join(".ark")
+        );
+        assert_eq!(
+            resolve_ark_folder(root, Some("scratch/../../other".into())),
+            root.join(".ark")
+        );
+        assert_eq!(
+            resolve_ark_folder(root, Some("/var/../scratch".into())),
+            root.join(".ark")
+        );
+    }
+}